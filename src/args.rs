@@ -0,0 +1,151 @@
+use crate::logger::{Logger, ScopedLogger};
+use zed_extension_api::serde_json::Value;
+
+const LOG: ScopedLogger = Logger::scoped("args");
+
+/// Converts the `args` of a debug configuration to the argument vector passed to the program.
+/// An array is used as is, with numbers and booleans converted to strings with a warning; a
+/// single string is split like a shell command line.
+pub fn program_args(args: &Value) -> Result<Vec<String>, String> {
+    match args {
+        Value::String(command_line) => split_command_line(command_line),
+        Value::Array(args) => Ok(args
+            .iter()
+            .map(|arg| match arg {
+                Value::String(arg) => arg.clone(),
+                other => {
+                    LOG.warn(&format!(
+                        "Program argument {} is not a string; passing it as \"{}\"",
+                        other, other
+                    ));
+                    other.to_string()
+                }
+            })
+            .collect()),
+        other => Err(format!(
+            "'args' must be an array of strings or a single string, got {}",
+            other
+        )),
+    }
+}
+
+/// Splits a command line into arguments at unquoted whitespace. Single quotes preserve their
+/// content literally, double quotes allow `\"` and `\\` escapes, and a backslash outside quotes
+/// escapes the next character. Quotes with nothing in them produce an empty argument.
+pub fn split_command_line(command_line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = command_line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                args.extend(current.take());
+            }
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err(unterminated(command_line, '\'')),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => arg.push(c),
+                            Some(c) => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                            None => return Err(unterminated(command_line, '"')),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err(unterminated(command_line, '"')),
+                    }
+                }
+            }
+            '\\' => {
+                let arg = current.get_or_insert_with(String::new);
+                arg.push(chars.next().unwrap_or('\\'));
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+fn unterminated(command_line: &str, quote: char) -> String {
+    format!("Unterminated {} in 'args': {}", quote, command_line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed_extension_api::serde_json;
+
+    #[test]
+    fn splits_command_lines() {
+        let cases: [(&str, &[&str]); 14] = [
+            ("--port 5001", &["--port", "5001"]),
+            ("  spaced\t\targs \n", &["spaced", "args"]),
+            ("", &[]),
+            (
+                r#""path with spaces/data.json""#,
+                &["path with spaces/data.json"],
+            ),
+            // Empty quotes are an empty argument
+            (r#"a "" ''"#, &["a", "", ""]),
+            // Quotes join with adjacent text
+            (r#"--name="Jane Doe"x"#, &["--name=Jane Doex"]),
+            // Nested quotes of the other kind are literal
+            (r#""it's" '"quoted"'"#, &["it's", r#""quoted""#]),
+            // Escaped quotes
+            (r#""say \"hi\"" \"bare\""#, &[r#"say "hi""#, r#""bare""#]),
+            (r#""back\\slash" "keep\n""#, &[r"back\slash", r"keep\n"]),
+            ("'no \\escapes'", &[r"no \escapes"]),
+            // An escaped space does not split
+            (r"one\ arg two", &["one arg", "two"]),
+            // A trailing backslash is kept
+            (r"trailing\", &[r"trailing\"]),
+            (r"a \", &["a", r"\"]),
+            (r#"'' """#, &["", ""]),
+        ];
+        for (command_line, expected) in cases {
+            assert_eq!(
+                split_command_line(command_line).unwrap(),
+                expected,
+                "command line: {command_line}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        for command_line in [r#"--name "Jane"#, "'open", r#""escaped\""#, r#""trailing\"#] {
+            assert!(
+                split_command_line(command_line).is_err(),
+                "command line: {command_line}"
+            );
+        }
+    }
+
+    #[test]
+    fn converts_args_arrays_and_strings() {
+        assert_eq!(
+            program_args(&serde_json::json!(["--port", 5001, true, "a b"])).unwrap(),
+            ["--port", "5001", "true", "a b"]
+        );
+        assert_eq!(
+            program_args(&serde_json::json!("--port 5001 'a b'")).unwrap(),
+            ["--port", "5001", "a b"]
+        );
+        assert!(program_args(&serde_json::json!({"port": 5001})).is_err());
+    }
+}
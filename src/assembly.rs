@@ -0,0 +1,184 @@
+use std::path::Path;
+
+/// Signature the .NET SDK embeds in every apphost, preceded by the offset of the bundle header,
+/// which is zero unless the application was published as a single file
+const BUNDLE_SIGNATURE: [u8; 32] = [
+    0x8b, 0x12, 0x02, 0xb9, 0x6a, 0x61, 0x20, 0x38, 0x72, 0x7b, 0x93, 0x02, 0x14, 0xd7, 0xa0, 0x32,
+    0x13, 0xf5, 0xb9, 0xe6, 0xef, 0xae, 0x33, 0x18, 0xee, 0x3b, 0x2d, 0xce, 0x24, 0xb3, 0x6a, 0xae,
+];
+
+/// `TargetFrameworkAttribute` value prefix of assemblies built for the .NET Framework
+const NET_FRAMEWORK_MONIKER: &[u8] = b".NETFramework,Version=";
+
+/// Index of the CLI header among the data directories of a PE optional header
+const CLI_HEADER_DIRECTORY: usize = 14;
+
+/// What kind of code a program consists of, as far as its headers tell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    /// A .NET (Core) assembly, or an apphost or single-file bundle starting one
+    CoreClr,
+    /// An assembly built for the .NET Framework
+    NetFramework,
+    /// Native code without CLR metadata, such as a NativeAOT-compiled executable
+    Native,
+}
+
+/// Classifies the program at `path` from its PE headers, and for executables the apphost
+/// signature. Returns `None` when the file cannot be read or is in no known format.
+pub fn classify(path: &Path) -> Option<TargetKind> {
+    let content = std::fs::read(path).ok()?;
+    if bundle_header_offset(&content).is_some() {
+        return Some(TargetKind::CoreClr);
+    }
+    if content.starts_with(b"MZ") {
+        return Some(match has_cli_header(&content)? {
+            false => TargetKind::Native,
+            true if contains(&content, NET_FRAMEWORK_MONIKER) => TargetKind::NetFramework,
+            true => TargetKind::CoreClr,
+        });
+    }
+    // ELF and Mach-O (32/64-bit, either byte order) executables without an apphost signature
+    let is_native = content.starts_with(b"\x7fELF")
+        || [0xfeedface_u32, 0xfeedfacf, 0xcefaedfe, 0xcffaedfe]
+            .iter()
+            .any(|magic| content.starts_with(&magic.to_be_bytes()));
+    is_native.then_some(TargetKind::Native)
+}
+
+/// Whether `path` is an executable published with `PublishSingleFile`, whose assemblies are
+/// bundled into the executable and loaded from memory
+pub fn is_single_file_bundle(path: &Path) -> bool {
+    std::fs::read(path)
+        .ok()
+        .and_then(|content| bundle_header_offset(&content))
+        .is_some_and(|offset| offset != 0)
+}
+
+/// The bundle header offset stored before the apphost signature, if `content` is an apphost
+fn bundle_header_offset(content: &[u8]) -> Option<i64> {
+    let position = content
+        .windows(BUNDLE_SIGNATURE.len())
+        .position(|window| window == BUNDLE_SIGNATURE)
+        .filter(|&position| position >= 8)?;
+    Some(i64::from_le_bytes(
+        content[position - 8..position].try_into().ok()?,
+    ))
+}
+
+/// Whether the PE image has a CLI header, i.e. contains managed code. Only the DOS, COFF and
+/// optional headers are read.
+fn has_cli_header(content: &[u8]) -> Option<bool> {
+    let read_u16 = |offset: usize| {
+        Some(u16::from_le_bytes(
+            content.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    };
+    let read_u32 = |offset: usize| {
+        Some(u32::from_le_bytes(
+            content.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+
+    let pe_header = read_u32(0x3c)? as usize;
+    if content.get(pe_header..pe_header + 4)? != b"PE\0\0" {
+        return None;
+    }
+    let optional_header = pe_header + 24;
+    let data_directories = match read_u16(optional_header)? {
+        0x10b => optional_header + 96,
+        0x20b => optional_header + 112,
+        _ => return None,
+    };
+    let directory_count = read_u32(data_directories - 4)? as usize;
+    if directory_count <= CLI_HEADER_DIRECTORY {
+        return Some(false);
+    }
+    let cli_header = data_directories + CLI_HEADER_DIRECTORY * 8;
+    Some(read_u32(cli_header)? != 0 && read_u32(cli_header + 4)? != 0)
+}
+
+fn contains(content: &[u8], needle: &[u8]) -> bool {
+    content.windows(needle.len()).any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    /// An executable with the apphost signature, preceded by `bundle_offset`
+    fn apphost(prefix: &[u8], bundle_offset: i64) -> Vec<u8> {
+        let mut content = prefix.to_vec();
+        content.extend_from_slice(&[0; 64]);
+        content.extend_from_slice(&bundle_offset.to_le_bytes());
+        content.extend_from_slice(&BUNDLE_SIGNATURE);
+        content.extend_from_slice(&[0; 64]);
+        content
+    }
+
+    /// A PE32+ image whose CLI header directory is set if `managed`
+    fn pe_image(managed: bool, trailer: &[u8]) -> Vec<u8> {
+        const PE_HEADER: usize = 0x80;
+        let mut content = vec![0; 0x200];
+        content[..2].copy_from_slice(b"MZ");
+        content[0x3c..0x40].copy_from_slice(&(PE_HEADER as u32).to_le_bytes());
+        content[PE_HEADER..PE_HEADER + 4].copy_from_slice(b"PE\0\0");
+        let optional_header = PE_HEADER + 24;
+        content[optional_header..optional_header + 2].copy_from_slice(&0x20b_u16.to_le_bytes());
+        let data_directories = optional_header + 112;
+        content[data_directories - 4..data_directories].copy_from_slice(&16_u32.to_le_bytes());
+        if managed {
+            let cli_header = data_directories + CLI_HEADER_DIRECTORY * 8;
+            content[cli_header..cli_header + 4].copy_from_slice(&0x2008_u32.to_le_bytes());
+            content[cli_header + 4..cli_header + 8].copy_from_slice(&0x48_u32.to_le_bytes());
+        }
+        content.extend_from_slice(trailer);
+        content
+    }
+
+    #[test]
+    fn detects_single_file_bundles() {
+        let dir = TempDir::new();
+        let bundle = dir.write("publish/App", apphost(b"\x7fELF", 0x1234_5678));
+        let apphost_only = dir.write("bin/App", apphost(b"\x7fELF", 0));
+        let assembly = dir.write("bin/App.dll", pe_image(true, b""));
+
+        assert!(is_single_file_bundle(&bundle));
+        assert!(!is_single_file_bundle(&apphost_only));
+        assert!(!is_single_file_bundle(&assembly));
+        assert!(!is_single_file_bundle(&dir.path().join("missing")));
+
+        assert_eq!(classify(&bundle), Some(TargetKind::CoreClr));
+        assert_eq!(classify(&apphost_only), Some(TargetKind::CoreClr));
+    }
+
+    #[test]
+    fn classifies_programs_by_their_headers() {
+        let dir = TempDir::new();
+        let cases = [
+            ("core.dll", pe_image(true, b""), Some(TargetKind::CoreClr)),
+            (
+                "framework.exe",
+                pe_image(true, b".NETFramework,Version=v4.8"),
+                Some(TargetKind::NetFramework),
+            ),
+            ("native.exe", pe_image(false, b""), Some(TargetKind::Native)),
+            (
+                "aot",
+                b"\x7fELF\x02\x01\x01".to_vec(),
+                Some(TargetKind::Native),
+            ),
+            (
+                "macho",
+                0xcffaedfe_u32.to_be_bytes().to_vec(),
+                Some(TargetKind::Native),
+            ),
+            ("truncated.dll", b"MZ\0\0".to_vec(), None),
+            ("script.sh", b"#!/bin/sh\n".to_vec(), None),
+        ];
+        for (name, content, expected) in cases {
+            assert_eq!(classify(&dir.write(name, content)), expected, "{name}");
+        }
+    }
+}
@@ -1,20 +1,305 @@
-use crate::logger::Logger;
+use crate::error::BinaryManagerError;
+use crate::logger::{Logger, ScopedLogger};
+use crate::paths::{
+    expand_path, is_absolute_path, normalize_windows_path, to_long_windows_path, WINDOWS_MAX_PATH,
+};
+use crate::platform;
+use crate::settings::{AdapterSettings, AutoUpdate, PathScope};
+use crate::sha256::Sha256;
+use crate::version::{compare_versions, is_prerelease};
 use fs_extra::dir;
-use std::sync::OnceLock;
-use zed_extension_api::{self as zed, DownloadedFileType, GithubReleaseOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use zed_extension_api::http_client::{HttpMethod, HttpRequest, RedirectPolicy};
+use zed_extension_api::process::Command;
+use zed_extension_api::{
+    self as zed, serde_json, DownloadedFileType, GithubReleaseOptions, Worktree,
+};
+
+const LOG: ScopedLogger = Logger::scoped("binary_manager");
 
 /// GitHub release version information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdapterVersion {
     /// Release tag name (version)
     pub tag_name: String,
     /// Download URL for the release asset
     pub download_url: String,
+    /// Download URL for the `{asset}.sha256` checksum file, if the release publishes one
+    pub checksum_url: Option<String>,
+}
+
+/// How a downloaded release asset is unpacked
+#[derive(Debug, Clone, Copy)]
+enum AssetFormat {
+    /// An archive type `download_file` extracts itself
+    Archive(DownloadedFileType),
+    /// A `.tar.xz` archive, which `download_file` cannot extract, unpacked with the system `tar`
+    TarXz,
+    /// A bare executable placed directly into the version directory
+    RawExecutable,
+}
+
+/// Whether netcoredbg releases can be downloaded for the current platform
+#[derive(Debug)]
+enum PlatformSupport {
+    /// Releases publish an asset for this platform, possibly with a caveat worth logging
+    Supported {
+        asset_name: String,
+        warning: Option<&'static str>,
+    },
+    /// No release asset exists, but a netcoredbg built for this platform can still be configured
+    UserPathRequired { reason: String },
+}
+
+/// A netcoredbg version found in the install root
+#[derive(Debug, Clone)]
+pub struct InstalledVersion {
+    /// Release tag the version directory is named after
+    pub tag: String,
+    /// Absolute path to the executable
+    pub path: std::path::PathBuf,
+    /// Size of the executable in bytes
+    pub size: u64,
+    /// Last modification time of the version directory, i.e. roughly when it was installed
+    pub installed_at: std::time::SystemTime,
+}
+
+/// Last successful latest-release lookup, persisted to avoid GitHub rate limits
+#[derive(Debug, Serialize, Deserialize)]
+struct ReleaseCache {
+    /// Repository (`owner/repo`) the release was fetched from
+    repo: String,
+    /// Platform asset the download URL points to
+    asset_name: String,
+    /// Whether pre-releases were allowed for the lookup
+    #[serde(default)]
+    pre_release: bool,
+    /// Unix timestamp (seconds) of the lookup
+    fetched_at: u64,
+    version: AdapterVersion,
+}
+
+/// The binary chosen by the last successful resolution, persisted so that later sessions can
+/// skip release resolution entirely
+#[derive(Debug, Serialize, Deserialize)]
+struct InstallManifest {
+    /// Absolute path to the executable
+    binary_path: String,
+    /// Release tag of the installed version
+    version: String,
+    /// Unix timestamp (seconds) the version directory was installed
+    installed_at: u64,
+    /// Unix timestamp (seconds) the binary was last resolved and recorded
+    resolved_at: u64,
+    /// SHA256 of the executable when it was recorded
+    checksum: String,
+    /// Digest over every file of the version directory, recorded while `verify_full_install`
+    /// is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    install_checksum: Option<String>,
+    /// Unix timestamp (seconds) of the last check for a newer release
+    #[serde(default)]
+    update_checked_at: u64,
+    /// Release tags whose fresh install failed validation, skipped by automatic updates
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    bad_versions: Vec<String>,
+    /// An update whose first debug session has not yet shown that it starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    first_launch: Option<FirstLaunch>,
+}
+
+/// The first launch of a freshly updated version. netcoredbg writes its engine log once Zed
+/// talks to it, so a log untouched since the launch means that it exited immediately.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct FirstLaunch {
+    /// Release tag of the update
+    version: String,
+    /// Engine log the launch writes, unset until the update is launched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    engine_log: Option<String>,
+    /// Unix timestamp (seconds) of the launch
+    #[serde(default)]
+    launched_at: u64,
+}
+
+/// Logs installation milestones together with the time spent in each phase.
+/// The debug adapter extension API offers no status or notification channel, so the
+/// debug log is the only place progress can be surfaced.
+struct InstallProgress {
+    version: String,
+    started: std::time::Instant,
+    current_phase: Option<(&'static str, std::time::Instant)>,
+}
+
+impl InstallProgress {
+    fn new(version: &str) -> Self {
+        LOG.info(&format!("Installing netcoredbg {}…", version));
+        Self {
+            version: version.to_string(),
+            started: std::time::Instant::now(),
+            current_phase: None,
+        }
+    }
+
+    /// Ends the current phase (logging its duration) and starts the next one
+    fn phase(&mut self, name: &'static str) {
+        self.finish_phase();
+        LOG.debug(&format!("[netcoredbg {}] {}…", self.version, name));
+        self.current_phase = Some((name, std::time::Instant::now()));
+    }
+
+    fn finish_phase(&mut self) {
+        if let Some((name, started)) = self.current_phase.take() {
+            LOG.debug(&format!(
+                "[netcoredbg {}] {} took {:?}",
+                self.version,
+                name,
+                started.elapsed()
+            ));
+        }
+    }
+}
+
+impl Drop for InstallProgress {
+    fn drop(&mut self) {
+        self.finish_phase();
+        LOG.info(&format!(
+            "[netcoredbg {}] install ended after {:?}",
+            self.version,
+            self.started.elapsed()
+        ));
+    }
+}
+
+/// Lock file preventing several extension instances from installing the same version at once.
+/// The lock is released when the guard is dropped.
+struct InstallLock {
+    path: std::path::PathBuf,
+}
+
+impl InstallLock {
+    /// How long to wait for another instance to finish installing
+    const WAIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(180);
+    const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+    /// Locks older than this are assumed to belong to a crashed instance and are broken
+    const STALE_AFTER: std::time::Duration = std::time::Duration::from_secs(300);
+
+    fn acquire(path: &std::path::Path) -> Result<Self, BinaryManagerError> {
+        let started = std::time::Instant::now();
+
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+            {
+                Ok(_) => {
+                    LOG.debug(&format!("Acquired install lock {}", path.display()));
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if !Self::is_held(path) {
+                        LOG.warn(&format!("Breaking stale install lock {}", path.display()));
+                        let _ = std::fs::remove_file(path);
+                        continue;
+                    }
+                    if started.elapsed() > Self::WAIT_TIMEOUT {
+                        return Err(BinaryManagerError::FileSystem(format!(
+                            "Timed out after {:?} waiting for another instance to finish installing netcoredbg (lock file: {})",
+                            Self::WAIT_TIMEOUT,
+                            path.display()
+                        )));
+                    }
+                    std::thread::sleep(Self::POLL_INTERVAL);
+                }
+                Err(e) => {
+                    return Err(BinaryManagerError::FileSystem(format!(
+                        "Failed to create install lock {}: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Whether the lock file exists and is recent enough to belong to a running install
+    fn is_held(path: &std::path::Path) -> bool {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age < Self::STALE_AFTER))
+    }
+}
+
+impl Drop for InstallLock {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            LOG.warn(&format!(
+                "Failed to release install lock {}: {}",
+                self.path.display(),
+                e
+            ));
+        }
+    }
+}
+
+/// Directory an archive is downloaded and extracted into. It starts out empty, so files left by
+/// an earlier failed attempt are never merged into the install, and it is removed when the
+/// guard is dropped, whether or not the install succeeded.
+struct ScratchDir {
+    path: std::path::PathBuf,
+}
+
+impl ScratchDir {
+    fn create(path: std::path::PathBuf) -> Result<Self, BinaryManagerError> {
+        if path.exists() {
+            LOG.debug(&format!(
+                "Removing leftover temp directory {}",
+                path.display()
+            ));
+            std::fs::remove_dir_all(&path).map_err(|e| {
+                BinaryManagerError::FileSystem(format!(
+                    "Failed to remove leftover temp directory {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        }
+        std::fs::create_dir_all(&path).map_err(|e| {
+            BinaryManagerError::FileSystem(format!("Failed to create temp directory: {}", e))
+        })?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.path) {
+            LOG.warn(&format!(
+                "Failed to remove temp directory {}: {}",
+                self.path.display(),
+                e
+            ));
+        }
+    }
 }
 
 pub struct BinaryManager {
-    /// Cached path to the netcoredbg binary - set once and reused
-    cached_binary_path: OnceLock<String>,
+    /// Cached path to the netcoredbg binary, cleared when a reinstall is forced
+    cached_binary_path: Mutex<Option<String>>,
+    /// Whether the `force_reinstall` setting was already honoured in this session
+    force_reinstall_done: AtomicBool,
+    /// Read-only install root and the temp directory installs fall back to instead
+    fallback_root: Mutex<Option<(std::path::PathBuf, std::path::PathBuf)>>,
+    /// Version reported by `netcoredbg --version` after the last verified install
+    installed_version: Mutex<Option<String>>,
+    /// Binary that last passed the preflight check, which is not repeated for it
+    preflight_passed: Mutex<Option<String>>,
 }
 
 impl Default for BinaryManager {
@@ -26,15 +311,55 @@ impl Default for BinaryManager {
 impl BinaryManager {
     const GITHUB_OWNER: &str = "marcptrs";
     const GITHUB_REPO: &str = "netcoredbg";
+    const VERSION_DIR_PREFIX: &str = "netcoredbg_v";
+    const STAGING_DIR_SUFFIX: &str = ".partial";
+    const DEFAULT_KEEP_VERSIONS: usize = 2;
+    const DEFAULT_RETRY_ATTEMPTS: u32 = 3;
+    const DEFAULT_RETRY_DELAY_MS: u64 = 500;
+    const DEFAULT_RELEASE_CACHE_TTL_HOURS: u64 = 24;
+    const DEFAULT_TIMEOUT_SECS: u64 = 30;
+    /// Executables smaller than this are treated as truncated downloads
+    const MIN_BINARY_SIZE: u64 = 16 * 1024;
+    const RELEASE_CACHE_FILE: &str = "netcoredbg_release_cache.json";
+    /// Engine log the first launch of an update writes, unless the configuration names one
+    const FIRST_LAUNCH_LOG_FILE: &str = "netcoredbg_first_launch.log";
+    const MANIFEST_FILE: &str = "manifest.json";
+    /// Directory in the install root symbols downloaded from symbol servers are cached in
+    const SYMBOL_CACHE_DIR: &str = "symbol_cache";
+    /// Minimum time between two checks for a release newer than the reused install
+    const UPDATE_NOTICE_INTERVAL_SECS: u64 = 24 * 60 * 60;
+    /// Space needed relative to the archive size: the extracted temp copy plus the final install
+    const EXTRACTION_SPACE_MULTIPLIER: u64 = 4;
+    /// Suffixes of supported asset formats, stripped to compare assets regardless of compression
+    const ASSET_SUFFIXES: &[&str] = &[".tar.gz", ".tgz", ".tar.xz", ".txz", ".zip", ".exe"];
+    /// Trailing stderr lines of a failed launch included in the error
+    const STDERR_TAIL_LINES: usize = 10;
 
     pub fn new() -> Self {
         Self {
-            cached_binary_path: OnceLock::new(),
+            cached_binary_path: Mutex::new(None),
+            force_reinstall_done: AtomicBool::new(false),
+            fallback_root: Mutex::new(None),
+            installed_version: Mutex::new(None),
+            preflight_passed: Mutex::new(None),
+        }
+    }
+
+    fn cached_path(&self) -> Option<String> {
+        self.cached_binary_path
+            .lock()
+            .ok()
+            .and_then(|path| path.clone())
+    }
+
+    fn set_cached_path(&self, path: Option<String>) {
+        if let Ok(mut cached_binary_path) = self.cached_binary_path.lock() {
+            *cached_binary_path = path;
         }
     }
 
     fn get_executable_name() -> &'static str {
-        match zed::current_platform().0 {
+        match platform::os() {
             zed::Os::Windows => "netcoredbg.exe",
             _ => "netcoredbg",
         }
@@ -50,287 +375,3252 @@ impl BinaryManager {
     /// - netcoredbg-osx-x64.tar.gz
     /// - netcoredbg-win-x64.zip
     /// - netcoredbg-win-x86.zip
-    fn get_platform_asset_name() -> Result<String, String> {
-        let (platform, arch) = zed::current_platform();
+    ///
+    /// On musl-based Linux the `linux-musl-*` variant is requested instead, and the
+    /// `force_asset` setting overrides detection entirely.
+    fn platform_support(settings: &AdapterSettings) -> PlatformSupport {
+        if let Some(force_asset) = &settings.force_asset {
+            return PlatformSupport::Supported {
+                asset_name: force_asset.clone(),
+                warning: None,
+            };
+        }
 
-        let (platform_arch, extension) = match (platform, arch) {
-            (zed_extension_api::Os::Linux, zed_extension_api::Architecture::Aarch64) => {
-                ("linux-arm64", ".tar.gz")
-            }
-            (zed_extension_api::Os::Linux, zed_extension_api::Architecture::X86) => {
-                ("linux-x86", ".tar.gz")
-            }
-            (zed_extension_api::Os::Linux, zed_extension_api::Architecture::X8664) => {
-                ("linux-x64", ".tar.gz")
-            }
-            (zed_extension_api::Os::Mac, zed_extension_api::Architecture::Aarch64) => {
-                ("osx-arm64", ".tar.gz")
-            }
-            (zed_extension_api::Os::Mac, zed_extension_api::Architecture::X86) => {
-                ("osx-x86", ".tar.gz")
-            }
-            (zed_extension_api::Os::Mac, zed_extension_api::Architecture::X8664) => {
-                ("osx-x64", ".tar.gz")
-            }
-            (zed_extension_api::Os::Windows, zed_extension_api::Architecture::Aarch64) => {
-                ("win-x64", ".zip")
-            }
-            (zed_extension_api::Os::Windows, zed_extension_api::Architecture::X86) => {
-                ("win-x86", ".zip")
-            }
-            (zed_extension_api::Os::Windows, zed_extension_api::Architecture::X8664) => {
-                ("win-x64", ".zip")
+        let (platform, arch) = platform::current();
+
+        // `zed::Architecture` is matched exhaustively, so architectures added to the extension
+        // API in the future surface as a compile error here rather than a silent fallback
+        let (platform_arch, extension, warning) = match (platform, arch) {
+            (zed::Os::Linux, zed::Architecture::Aarch64) => ("linux-arm64", ".tar.gz", None),
+            (zed::Os::Linux, zed::Architecture::X86) => ("linux-x86", ".tar.gz", None),
+            (zed::Os::Linux, zed::Architecture::X8664) => ("linux-x64", ".tar.gz", None),
+            (zed::Os::Mac, zed::Architecture::Aarch64) => ("osx-arm64", ".tar.gz", None),
+            (zed::Os::Mac, zed::Architecture::X86) => {
+                return PlatformSupport::UserPathRequired {
+                    reason: "netcoredbg publishes no builds for 32-bit macOS".to_string(),
+                };
             }
+            (zed::Os::Mac, zed::Architecture::X8664) => ("osx-x64", ".tar.gz", None),
+            (zed::Os::Windows, zed::Architecture::Aarch64) => (
+                "win-x64",
+                ".zip",
+                Some(
+                    "netcoredbg publishes no Windows ARM64 build, using the x64 build under emulation. \
+                     Debugging native ARM64 .NET processes may fail; run them as x64 or configure an ARM64 netcoredbg.",
+                ),
+            ),
+            (zed::Os::Windows, zed::Architecture::X86) => ("win-x86", ".zip", None),
+            (zed::Os::Windows, zed::Architecture::X8664) => ("win-x64", ".zip", None),
         };
-        Ok(format!("netcoredbg-{}{}", platform_arch, extension))
-    }
 
-    /// Fetches the latest release information from GitHub
-    fn fetch_latest_release(&self) -> Result<AdapterVersion, String> {
-        let release = zed::latest_github_release(
-            &format!("{}/{}", Self::GITHUB_OWNER, Self::GITHUB_REPO),
-            GithubReleaseOptions {
-                require_assets: true,
-                pre_release: false,
-            },
-        )
-        .map_err(|e| format!("Failed to fetch latest release: {}", e))?;
+        if platform == zed::Os::Linux && Self::is_musl() {
+            if let Some(arch) = platform_arch.strip_prefix("linux-") {
+                return PlatformSupport::Supported {
+                    asset_name: format!("netcoredbg-linux-musl-{}{}", arch, extension),
+                    warning,
+                };
+            }
+        }
 
-        let asset_name = Self::get_platform_asset_name()?;
+        PlatformSupport::Supported {
+            asset_name: format!("netcoredbg-{}{}", platform_arch, extension),
+            warning,
+        }
+    }
 
-        let asset = release
-            .assets
-            .iter()
-            .find(|asset| asset.name == asset_name)
-            .ok_or_else(|| {
-                format!(
-                    "No compatible asset found for platform. Looking for: '{}'. Available assets: [{}]",
-                    asset_name,
-                    release.assets.iter().map(|a| a.name.as_str()).collect::<Vec<_>>().join(", ")
-                )
-            })?;
+    fn get_platform_asset_name(settings: &AdapterSettings) -> Result<String, BinaryManagerError> {
+        match Self::platform_support(settings) {
+            PlatformSupport::Supported { asset_name, .. } => Ok(asset_name),
+            PlatformSupport::UserPathRequired { reason } => {
+                Err(BinaryManagerError::UnsupportedPlatform { reason })
+            }
+        }
+    }
 
-        Ok(AdapterVersion {
-            tag_name: release.version,
-            download_url: asset.download_url.clone(),
+    /// Detects a musl-based Linux (e.g. Alpine), on which the glibc-linked builds fail to start.
+    /// The extension sandbox cannot list `/lib`, so this asks `ldd --version`, which musl
+    /// answers on stderr.
+    fn is_musl() -> bool {
+        static IS_MUSL: OnceLock<bool> = OnceLock::new();
+
+        *IS_MUSL.get_or_init(|| {
+            let is_musl = Command::new("ldd")
+                .arg("--version")
+                .output()
+                .is_ok_and(|output| {
+                    Self::is_musl_ldd_output(&String::from_utf8_lossy(&output.stdout))
+                        || Self::is_musl_ldd_output(&String::from_utf8_lossy(&output.stderr))
+                });
+            LOG.info(&format!("musl libc detected: {}", is_musl));
+            is_musl
         })
     }
 
-    /// Downloads and extracts the netcoredbg binary, returning the path to the executable
-    fn download_and_extract_binary(&self) -> Result<String, String> {
-        let version = self.fetch_latest_release()?;
-        let asset_name = Self::get_platform_asset_name()?;
-
-        let file_type = if asset_name.ends_with(".zip") {
-            DownloadedFileType::Zip
-        } else if asset_name.ends_with(".tar.gz") {
-            DownloadedFileType::GzipTar
-        } else {
-            return Err(format!("Unsupported file type for asset: {}", asset_name));
-        };
+    /// Whether `ldd --version` output comes from musl (glibc prints "GNU libc"/"GLIBC")
+    fn is_musl_ldd_output(output: &str) -> bool {
+        output.to_ascii_lowercase().contains("musl")
+    }
 
-        let temp_dir = std::path::PathBuf::from(format!("temp_netcoredbg_v{}", version.tag_name));
-        std::fs::create_dir_all(&temp_dir)
-            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    /// Returns the `owner/repo` to fetch releases from, validating the `github_repo` setting
+    fn github_repo(settings: &AdapterSettings) -> Result<String, BinaryManagerError> {
+        let Some(repo) = settings.github_repo.as_deref().map(str::trim) else {
+            return Ok(format!("{}/{}", Self::GITHUB_OWNER, Self::GITHUB_REPO));
+        };
 
-        let temp_dir_str = temp_dir
-            .clone()
-            .into_os_string()
-            .into_string()
-            .map_err(|_| "Failed to convert temp_dir path to string".to_string())?;
-        zed_extension_api::download_file(&version.download_url, &temp_dir_str, file_type)
-            .map_err(|e| format!("Failed to download netcoredbg: {}", e))?;
+        let is_valid_part = |part: &str| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
 
-        let version_dir = std::path::PathBuf::from(format!("netcoredbg_v{}", version.tag_name));
-        std::fs::create_dir_all(&version_dir)
-            .map_err(|e| format!("Failed to create version directory: {}", e))?;
+        match repo.split_once('/') {
+            Some((owner, name)) if is_valid_part(owner) && is_valid_part(name) => {
+                Ok(repo.to_string())
+            }
+            _ => Err(BinaryManagerError::InvalidSetting {
+                setting: "github_repo",
+                message: format!(
+                    "'{}'. Expected the form 'owner/repo', e.g. 'Samsung/netcoredbg'",
+                    repo
+                ),
+            }),
+        }
+    }
 
-        self.copy_extracted_content(&temp_dir, &version_dir)?;
+    /// Resolves the directory netcoredbg versions are installed into: the `install_dir` setting,
+    /// with relative values resolved against the worktree root, or the extension's work directory.
+    /// The directory itself is only created once something is installed into it.
+    fn install_root(
+        settings: &AdapterSettings,
+        worktree: &Worktree,
+    ) -> Result<std::path::PathBuf, BinaryManagerError> {
+        let Some(install_dir) = settings.install_dir.as_deref() else {
+            return Self::absolute_path(std::path::Path::new("."));
+        };
 
-        let exe_name = Self::get_executable_name();
-        let binary_path = version_dir.join(exe_name);
+        let env: HashMap<String, String> = worktree.shell_env().into_iter().collect();
+        let expanded = expand_path(install_dir, &env).map_err(|message| {
+            BinaryManagerError::InvalidSetting {
+                setting: "install_dir",
+                message,
+            }
+        })?;
+        let path = std::path::PathBuf::from(expanded);
 
-        if !binary_path.exists() {
-            return Err(format!(
-                "netcoredbg executable not found at: {}",
-                binary_path.display()
-            ));
-        }
+        Ok(if path.is_absolute() {
+            path
+        } else {
+            std::path::Path::new(&worktree.root_path()).join(path)
+        })
+    }
 
-        std::fs::remove_dir_all(temp_dir)
-            .map_err(|e| format!("Failed to remove temp directory: {}", e))?;
+    /// Directory symbols downloaded from symbol servers are cached in when `cachePath` is not
+    /// set: `symbol_cache` in the install root, so that it follows the `install_dir` setting.
+    /// Old versions are pruned by their directory names, which leaves the cache alone.
+    pub fn symbol_cache_dir(
+        settings: &AdapterSettings,
+        worktree: &Worktree,
+    ) -> Result<std::path::PathBuf, BinaryManagerError> {
+        Ok(Self::install_root(settings, worktree)?.join(Self::SYMBOL_CACHE_DIR))
+    }
 
-        let binary_path_str = Self::to_os_full_path_str(&binary_path)?;
-        zed::make_file_executable(&binary_path_str)
-            .map_err(|e| format!("Failed to make file executable: {}", e))?;
+    /// Creates the install root on first use, explaining permission problems
+    fn ensure_install_root(root: &std::path::Path) -> Result<(), BinaryManagerError> {
+        std::fs::create_dir_all(root).map_err(|e| {
+            BinaryManagerError::FileSystem(if e.kind() == std::io::ErrorKind::PermissionDenied {
+                format!(
+                    "Permission denied creating the netcoredbg install directory {}",
+                    root.display()
+                )
+            } else {
+                format!(
+                    "Failed to create the netcoredbg install directory {}: {}",
+                    root.display(),
+                    e
+                )
+            })
+        })
+    }
 
-        Ok(binary_path_str)
+    /// Returns the temp directory installs fall back to for `root` earlier in this session, if any
+    fn recorded_root(&self, root: std::path::PathBuf) -> std::path::PathBuf {
+        match self
+            .fallback_root
+            .lock()
+            .ok()
+            .and_then(|fallback| fallback.clone())
+        {
+            Some((read_only_root, fallback)) if read_only_root == root => fallback,
+            _ => root,
+        }
     }
 
-    /// Copies extracted content from temp_dir into version_dir, handling nested directory structure
-    fn copy_extracted_content(
+    /// Returns `root` if it can be written to. A read-only root is replaced by a user-scoped
+    /// directory in the OS temp directory, which is remembered for the rest of the session.
+    fn writable_root(
         &self,
-        temp_dir: &std::path::Path,
-        version_dir: &std::path::Path,
-    ) -> Result<(), String> {
-        let exe_name = Self::get_executable_name();
+        root: &std::path::Path,
+        worktree: &Worktree,
+    ) -> Result<std::path::PathBuf, BinaryManagerError> {
+        let check_writable = || -> std::io::Result<()> {
+            std::fs::create_dir_all(root)?;
+            let probe = root.join(".netcoredbg_write_test");
+            std::fs::write(&probe, b"")?;
+            std::fs::remove_file(probe)
+        };
 
-        let binary_source_path = self.find_binary_in_extracted_content(temp_dir, exe_name)?;
+        let e = match check_writable() {
+            Ok(()) => return Ok(root.to_path_buf()),
+            Err(e) => e,
+        };
+        if !matches!(
+            e.kind(),
+            std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem
+        ) {
+            Self::ensure_install_root(root)?;
+            return Err(BinaryManagerError::FileSystem(format!(
+                "The netcoredbg install directory {} is not writable: {}",
+                root.display(),
+                e
+            )));
+        }
 
-        let source_dir = binary_source_path
-            .parent()
-            .ok_or_else(|| "Binary has no parent directory".to_string())?;
+        let env: HashMap<String, String> = worktree.shell_env().into_iter().collect();
+        let temp_dir = ["TMPDIR", "TEMP", "TMP"]
+            .iter()
+            .find_map(|name| env.get(*name).filter(|value| !value.is_empty()))
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        let user = ["USER", "USERNAME"]
+            .iter()
+            .find_map(|name| env.get(*name).filter(|value| !value.is_empty()))
+            .map_or("user", String::as_str);
+        let fallback = temp_dir.join(format!("zed-netcoredbg-{}", user));
 
-        Logger::debug(&format!(
-            "Found binary at: {}, copying from: {}",
-            binary_source_path.display(),
-            source_dir.display()
+        LOG.warn(&format!(
+            "Install directory {} is read-only ({}). Installing netcoredbg into {} instead; \
+             this install is ephemeral and may be re-downloaded once the temp directory is cleaned. \
+             Set 'install_dir' to a writable location to avoid this.",
+            root.display(),
+            e,
+            fallback.display()
         ));
+        Self::ensure_install_root(&fallback)?;
+        if let Ok(mut fallback_root) = self.fallback_root.lock() {
+            *fallback_root = Some((root.to_path_buf(), fallback.clone()));
+        }
+        Ok(fallback)
+    }
 
-        let copy_options = dir::CopyOptions::new().content_only(true);
+    /// Returns the directory a given release tag is installed into
+    fn version_dir(root: &std::path::Path, tag: &str) -> std::path::PathBuf {
+        root.join(format!("{}{}", Self::VERSION_DIR_PREFIX, tag))
+    }
 
-        dir::copy(source_dir, version_dir, &copy_options).map_err(|e| {
-            format!(
-                "Failed to copy extracted content from {}: {}",
-                source_dir.display(),
-                e
-            )
-        })?;
+    /// Returns the staging directory a release is extracted into before being moved into place
+    fn staging_dir(root: &std::path::Path, tag: &str) -> std::path::PathBuf {
+        root.join(format!(
+            "{}{}{}",
+            Self::VERSION_DIR_PREFIX,
+            tag,
+            Self::STAGING_DIR_SUFFIX
+        ))
+    }
 
-        Ok(())
+    /// Returns the lock file guarding the installation of a given release tag
+    fn lock_path(root: &std::path::Path, tag: &str) -> std::path::PathBuf {
+        root.join(format!("{}{}.lock", Self::VERSION_DIR_PREFIX, tag))
     }
 
-    /// Recursively searches for the netcoredbg binary in the extracted content
-    fn find_binary_in_extracted_content(
-        &self,
-        search_dir: &std::path::Path,
-        exe_name: &str,
-    ) -> Result<std::path::PathBuf, String> {
-        fn find_binary_recursive(
-            dir: &std::path::Path,
-            exe_name: &str,
-        ) -> Result<Option<std::path::PathBuf>, String> {
-            let entries = std::fs::read_dir(dir)
-                .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+    /// Whether a directory name is a completed `netcoredbg_v*` install (not a staging directory)
+    fn is_version_dir_name(name: &str) -> bool {
+        name.starts_with(Self::VERSION_DIR_PREFIX) && !name.ends_with(Self::STAGING_DIR_SUFFIX)
+    }
 
-            for entry in entries {
-                let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-                let path = entry.path();
+    /// Removes staging directories left behind by installs that were interrupted
+    fn remove_partial_installs(root: &std::path::Path) {
+        let Ok(entries) = std::fs::read_dir(root) else {
+            return;
+        };
 
-                if path.is_file() && path.file_name().is_some_and(|name| name == exe_name) {
-                    return Ok(Some(path));
-                } else if path.is_dir() {
-                    if let Some(found) = find_binary_recursive(&path, exe_name)? {
-                        return Ok(Some(found));
-                    }
-                }
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(tag) = name
+                .strip_prefix(Self::VERSION_DIR_PREFIX)
+                .and_then(|rest| rest.strip_suffix(Self::STAGING_DIR_SUFFIX))
+            else {
+                continue;
+            };
+
+            // The staging directory of an install still running in another instance is kept
+            if InstallLock::is_held(&Self::lock_path(root, tag)) {
+                continue;
             }
-            Ok(None)
-        }
 
-        find_binary_recursive(search_dir, exe_name)?.ok_or_else(|| {
-            format!(
-                "Could not find {} binary in extracted content at {}",
-                exe_name,
-                search_dir.display()
-            )
-        })
+            match std::fs::remove_dir_all(entry.path()) {
+                Ok(()) => LOG.info(&format!("Removed interrupted install: {}", name)),
+                Err(e) => LOG.warn(&format!(
+                    "Failed to remove interrupted install {}: {}",
+                    name, e
+                )),
+            }
+        }
     }
 
-    /// Gets the netcoredbg binary path, downloading if necessary
-    pub fn get_binary_path(&self, user_provided_path: Option<String>) -> Result<String, String> {
-        Logger::debug("Starting get_binary_path");
+    /// Fetches release information, serving latest-release lookups from the on-disk cache while
+    /// it is fresh and falling back to stale cached metadata when GitHub rate-limits the request
+    fn fetch_release(
+        &self,
+        root: &std::path::Path,
+        tag: Option<&str>,
+        settings: &AdapterSettings,
+    ) -> Result<AdapterVersion, BinaryManagerError> {
+        if tag.is_some() {
+            return self.query_release(tag, settings);
+        }
 
-        // Priority 1: User-provided path return as is without any validation
-        if let Some(user_path) = user_provided_path {
-            Logger::debug(&format!("Using user-provided path: {}", user_path));
-            return Ok(user_path);
+        let repo = Self::github_repo(settings)?;
+        let cache = Self::read_release_cache(root, &repo, settings);
+        if let Some(version) = cache
+            .as_ref()
+            .and_then(|cache| Self::fresh_cached_release(cache, settings))
+        {
+            return Ok(version);
         }
 
-        // Priority 2: Check in-memory cache
-        if let Some(cached_path) = self.cached_binary_path.get() {
-            if std::path::Path::new(cached_path).exists() {
-                Logger::debug(&format!("Using cached binary path: {}", cached_path));
-                return Ok(cached_path.clone());
+        match self.query_release(None, settings) {
+            Ok(version) => {
+                Self::write_release_cache(root, &repo, settings, &version);
+                Ok(version)
             }
-            Logger::debug("Cached binary no longer exists, will re-download");
+            Err(e) => Self::stale_release_fallback(cache, e),
         }
+    }
 
-        // Priority 3: Check existing binary on disk before downloading
-        Logger::debug("Fetching latest release info from GitHub to check for existing binary");
-        let version = self.fetch_latest_release()?;
-        Logger::debug(&format!("Found latest version: {}", version.tag_name));
-
-        let version_dir = std::path::PathBuf::from(format!("netcoredbg_v{}", version.tag_name));
-        let exe_name = Self::get_executable_name();
-        let existing_binary_path = Self::absolute_path(&version_dir.join(exe_name))?;
-
-        if existing_binary_path.exists() {
-            Logger::debug(&format!(
-                "Found existing binary on disk: {}",
-                existing_binary_path.display()
+    /// Returns the cached release while it is younger than `release_cache_ttl_hours`
+    fn fresh_cached_release(
+        cache: &ReleaseCache,
+        settings: &AdapterSettings,
+    ) -> Option<AdapterVersion> {
+        let age_secs = Self::unix_timestamp().saturating_sub(cache.fetched_at);
+        if age_secs < Self::release_cache_ttl_secs(settings) {
+            LOG.debug(&format!(
+                "Using cached release info for {} ({} fetched {}s ago)",
+                cache.repo, cache.version.tag_name, age_secs
             ));
-            // Convert PathBuf -> String safely for caching
-            let path_str = Self::to_os_full_path_str(&existing_binary_path)?;
-            let _ = self.cached_binary_path.set(path_str.clone());
-            return Ok(path_str);
+            return Some(cache.version.clone());
         }
-
-        // Priority 4: Download and extract from GitHub releases
-        Logger::debug("No existing binary found, downloading from GitHub");
-        let binary_path = self.download_and_extract_binary()?;
-        Logger::debug(&format!(
-            "Successfully downloaded and extracted to: {}",
-            binary_path
+        LOG.debug(&format!(
+            "Cached release info for {} is stale ({}s old)",
+            cache.repo, age_secs
         ));
+        None
+    }
 
-        let _ = self.cached_binary_path.set(binary_path.clone());
-
-        self.validate_binary(&binary_path)?;
-
-        Ok(binary_path)
+    /// Serves stale cached metadata when GitHub rate-limited the lookup, and surfaces any
+    /// other error
+    fn stale_release_fallback(
+        cache: Option<ReleaseCache>,
+        error: BinaryManagerError,
+    ) -> Result<AdapterVersion, BinaryManagerError> {
+        match cache {
+            Some(cache) if Self::is_rate_limit_error(&error) => {
+                LOG.warn(&format!(
+                    "Rate limited by GitHub ({}), using stale cached release {}",
+                    error, cache.version.tag_name
+                ));
+                Ok(cache.version)
+            }
+            _ => Err(error),
+        }
     }
 
-    /// Validates that the binary exists
-    fn validate_binary(&self, binary_path: &str) -> Result<(), String> {
-        let path = std::path::Path::new(binary_path);
+    fn release_cache_ttl_secs(settings: &AdapterSettings) -> u64 {
+        settings
+            .release_cache_ttl_hours
+            .unwrap_or(Self::DEFAULT_RELEASE_CACHE_TTL_HOURS)
+            * 60
+            * 60
+    }
 
-        if !path.exists() {
-            return Err(format!("netcoredbg binary not found at: {}", binary_path));
+    /// Whether a cold start can reuse `installed` without looking up the latest release: the
+    /// release cache is missing or stale, but the manifest's last update check or the install of
+    /// `installed` itself is within the release cache TTL. A fresh release cache is left to
+    /// `fetch_release`, which serves it without the network.
+    fn can_skip_release_lookup(
+        root: &std::path::Path,
+        settings: &AdapterSettings,
+        installed: &InstalledVersion,
+    ) -> bool {
+        let release_cached = Self::github_repo(settings)
+            .ok()
+            .and_then(|repo| Self::read_release_cache(root, &repo, settings))
+            .is_some_and(|cache| Self::fresh_cached_release(&cache, settings).is_some());
+        if release_cached {
+            return false;
         }
+        let checked_at = Self::load_manifest(root).map_or(0, |manifest| manifest.update_checked_at);
+        let installed_at = installed
+            .installed_at
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+        Self::unix_timestamp().saturating_sub(checked_at.max(installed_at))
+            < Self::release_cache_ttl_secs(settings)
+    }
 
-        if !path.is_file() {
-            return Err(format!("netcoredbg path is not a file: {}", binary_path));
-        }
+    fn unix_timestamp() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
 
-        Ok(())
+    /// Reads the cached latest-release metadata for `repo`, ignoring missing or corrupt files
+    fn read_release_cache(
+        root: &std::path::Path,
+        repo: &str,
+        settings: &AdapterSettings,
+    ) -> Option<ReleaseCache> {
+        let contents = std::fs::read_to_string(root.join(Self::RELEASE_CACHE_FILE)).ok()?;
+        let cache: ReleaseCache = match serde_json::from_str(&contents) {
+            Ok(cache) => cache,
+            Err(e) => {
+                LOG.warn(&format!("Ignoring corrupt release cache: {}", e));
+                return None;
+            }
+        };
+        let asset_name = Self::get_platform_asset_name(settings).ok()?;
+        let pre_release = settings.allow_prerelease.unwrap_or(false);
+        (cache.repo == repo && cache.asset_name == asset_name && cache.pre_release == pre_release)
+            .then_some(cache)
     }
 
-    fn absolute_path(path: &std::path::PathBuf) -> Result<std::path::PathBuf, String> {
-        if path.is_absolute() {
-            Ok(path.to_path_buf())
-        } else {
-            std::env::current_dir()
-                .map_err(|e| format!("Failed to get current dir: {}", e))
-                .map(|cwd| cwd.join(path))
+    fn write_release_cache(
+        root: &std::path::Path,
+        repo: &str,
+        settings: &AdapterSettings,
+        version: &AdapterVersion,
+    ) {
+        let Ok(asset_name) = Self::get_platform_asset_name(settings) else {
+            return;
+        };
+        let cache = ReleaseCache {
+            repo: repo.to_string(),
+            asset_name,
+            pre_release: settings.allow_prerelease.unwrap_or(false),
+            fetched_at: Self::unix_timestamp(),
+            version: version.clone(),
+        };
+        let result = serde_json::to_string_pretty(&cache)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                Self::ensure_install_root(root).map_err(|e| e.to_string())?;
+                std::fs::write(root.join(Self::RELEASE_CACHE_FILE), json).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            LOG.warn(&format!("Failed to write release cache: {}", e));
         }
     }
 
-    fn to_os_full_path_str(path: &std::path::PathBuf) -> Result<String, String> {
-        let los = path.to_string_lossy();
+    fn is_rate_limit_error(error: &BinaryManagerError) -> bool {
+        let BinaryManagerError::ReleaseFetch { message, .. } = error else {
+            return false;
+        };
+        let message = message.to_ascii_lowercase();
+        message.contains("rate limit") || message.contains("403") || message.contains("429")
+    }
 
-        let s = match zed::current_platform().0 {
-            zed::Os::Windows => {
-                // Remove leading '/' if it precedes a drive letter (e.g. "/C:/path")
-                if los.starts_with('/') && los.chars().nth(2) == Some(':') {
-                    los[1..].to_string()
-                } else {
-                    los.to_string()
-                }
-            }
-            _ => los.to_string(),
+    /// Queries GitHub for release information, either for the given tag or the latest release
+    fn query_release(
+        &self,
+        tag: Option<&str>,
+        settings: &AdapterSettings,
+    ) -> Result<AdapterVersion, BinaryManagerError> {
+        let repo = Self::github_repo(settings)?;
+
+        let token = settings.github_token.as_deref();
+        let pre_release = settings.allow_prerelease.unwrap_or(false);
+
+        let release = match tag {
+            Some(tag) => self.with_retry(
+                "release lookup",
+                settings,
+                || match token {
+                    Some(token) => Self::fetch_release_rest(&repo, Some(tag), pre_release, token),
+                    None => zed::github_release_by_tag_name(&repo, tag),
+                },
+                |message| BinaryManagerError::ReleaseFetch {
+                    repo: repo.clone(),
+                    tag: Some(tag.to_string()),
+                    message,
+                    available_tags: Self::list_release_tags(&repo, token),
+                },
+            )?,
+            None => self.with_retry(
+                "release lookup",
+                settings,
+                || match token {
+                    Some(token) => Self::fetch_release_rest(&repo, None, pre_release, token),
+                    None => zed::latest_github_release(
+                        &repo,
+                        GithubReleaseOptions {
+                            require_assets: true,
+                            pre_release,
+                        },
+                    ),
+                },
+                |message| BinaryManagerError::ReleaseFetch {
+                    repo: repo.clone(),
+                    tag: None,
+                    message,
+                    available_tags: Vec::new(),
+                },
+            )?,
         };
 
-        Ok(s)
+        let asset_name = Self::get_platform_asset_name(settings)?;
+
+        // Forks may publish the same build with a different compression, so an asset with the
+        // same name but another supported format is accepted when the exact name is missing
+        let asset_stem = Self::asset_stem(&asset_name);
+        let asset = release
+            .assets
+            .iter()
+            .find(|asset| asset.name == asset_name)
+            .or_else(|| {
+                release.assets.iter().find(|asset| {
+                    Self::asset_stem(&asset.name) == asset_stem
+                        && Self::asset_format(&asset.name).is_ok()
+                })
+            })
+            .ok_or_else(|| {
+                let mut notes = Vec::new();
+                if settings.force_asset.is_none() && Self::is_musl() {
+                    notes.push(
+                        "This system uses musl libc (e.g. Alpine) but the release has no musl build, \
+                         and the glibc builds of netcoredbg will not start here. Install glibc compatibility \
+                         (e.g. 'apk add gcompat'), set 'force_asset' to a specific asset name, \
+                         or point the 'binary' setting at a musl-compatible netcoredbg"
+                            .to_string(),
+                    );
+                }
+                if tag.is_some() {
+                    notes.push(format!(
+                        "Available tags: [{}]",
+                        Self::list_release_tags(&repo, token).join(", ")
+                    ));
+                }
+                BinaryManagerError::AssetNotFound {
+                    repo: repo.clone(),
+                    release: release.version.clone(),
+                    wanted: asset_name.clone(),
+                    available: release.assets.iter().map(|a| a.name.clone()).collect(),
+                    note: (!notes.is_empty()).then(|| notes.join(". ")),
+                }
+            })?;
+
+        if asset.name != asset_name {
+            LOG.info(&format!(
+                "Asset '{}' not found, using '{}' instead",
+                asset_name, asset.name
+            ));
+        }
+
+        let checksum_name = format!("{}.sha256", asset.name);
+        let checksum_url = release
+            .assets
+            .iter()
+            .find(|asset| asset.name == checksum_name)
+            .map(|asset| asset.download_url.clone());
+
+        Ok(AdapterVersion {
+            tag_name: release.version,
+            download_url: asset.download_url.clone(),
+            checksum_url,
+        })
+    }
+
+    /// Sends a GET request to the GitHub REST API, authenticated when a token is given, and
+    /// returns the parsed JSON body. Errors never contain the token.
+    fn github_api_get(url: &str, token: Option<&str>) -> Result<serde_json::Value, String> {
+        let mut builder = HttpRequest::builder()
+            .method(HttpMethod::Get)
+            .url(url)
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", "zed-netcoredbg")
+            .redirect_policy(RedirectPolicy::FollowAll);
+        if let Some(token) = token {
+            builder = builder.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let redact = |message: String| match token {
+            Some(token) => Self::redact(&message, token),
+            None => message,
+        };
+
+        let response = builder
+            .build()
+            .and_then(|request| request.fetch())
+            .map_err(redact)?;
+        let value: serde_json::Value =
+            serde_json::from_slice(&response.body).map_err(|e| redact(e.to_string()))?;
+
+        // The response status is not exposed, but GitHub error bodies carry a `message` field
+        if let Some(message) = value
+            .as_object()
+            .filter(|object| !object.contains_key("tag_name"))
+            .and_then(|object| object.get("message"))
+            .and_then(|message| message.as_str())
+        {
+            return Err(redact(format!("GitHub API error: {}", message)));
+        }
+
+        Ok(value)
+    }
+
+    /// Fetches a release through the GitHub REST API. Used instead of the extension API's
+    /// release lookup when `GITHUB_TOKEN` is set, since that lookup cannot authenticate.
+    fn fetch_release_rest(
+        repo: &str,
+        tag: Option<&str>,
+        pre_release: bool,
+        token: &str,
+    ) -> Result<zed::GithubRelease, String> {
+        let base = format!("https://api.github.com/repos/{}/releases", repo);
+        let value = match tag {
+            Some(tag) => Self::github_api_get(&format!("{}/tags/{}", base, tag), Some(token))?,
+            None if pre_release => {
+                let releases = Self::github_api_get(&format!("{}?per_page=30", base), Some(token))?;
+                releases
+                    .as_array()
+                    .and_then(|releases| {
+                        releases.iter().find(|release| {
+                            release.get("draft").and_then(|d| d.as_bool()) != Some(true)
+                                && release
+                                    .get("assets")
+                                    .and_then(|a| a.as_array())
+                                    .is_some_and(|assets| !assets.is_empty())
+                        })
+                    })
+                    .cloned()
+                    .ok_or_else(|| format!("No release with assets found in {}", repo))?
+            }
+            None => Self::github_api_get(&format!("{}/latest", base), Some(token))?,
+        };
+
+        let version = value
+            .get("tag_name")
+            .and_then(|tag| tag.as_str())
+            .ok_or_else(|| "GitHub release response has no tag_name".to_string())?
+            .to_string();
+        let assets = value
+            .get("assets")
+            .and_then(|assets| assets.as_array())
+            .map(|assets| {
+                assets
+                    .iter()
+                    .filter_map(|asset| {
+                        Some(zed::GithubReleaseAsset {
+                            name: asset.get("name")?.as_str()?.to_string(),
+                            download_url: asset.get("browser_download_url")?.as_str()?.to_string(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(zed::GithubRelease { version, assets })
+    }
+
+    /// Replaces every occurrence of `secret` in `message`
+    fn redact(message: &str, secret: &str) -> String {
+        if secret.is_empty() {
+            message.to_string()
+        } else {
+            message.replace(secret, "[REDACTED]")
+        }
+    }
+
+    /// Fills in `github_token` from `GITHUB_TOKEN` in the worktree's shell environment
+    fn with_env_token(settings: &AdapterSettings, worktree: &Worktree) -> AdapterSettings {
+        let mut settings = settings.clone();
+        settings.github_token = worktree
+            .shell_env()
+            .into_iter()
+            .find(|(name, _)| name == "GITHUB_TOKEN")
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|token| !token.is_empty());
+        settings
+    }
+
+    /// Lists release tags of the repository via the GitHub REST API, used to enrich error messages
+    fn list_release_tags(repo: &str, token: Option<&str>) -> Vec<String> {
+        let releases = match Self::github_api_get(
+            &format!(
+                "https://api.github.com/repos/{}/releases?per_page=100",
+                repo
+            ),
+            token,
+        ) {
+            Ok(serde_json::Value::Array(releases)) => releases,
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                LOG.warn(&format!("Failed to list releases for {}: {}", repo, e));
+                return Vec::new();
+            }
+        };
+
+        releases
+            .iter()
+            .filter_map(|release| release.get("tag_name")?.as_str().map(str::to_string))
+            .collect()
+    }
+
+    /// Runs `operation`, retrying transient failures with exponential backoff until the
+    /// `timeout_secs` budget is spent. Final failures are converted with `on_error`, or reported
+    /// as a timeout when the budget ran out.
+    ///
+    /// Host calls are synchronous and cannot be cancelled from the extension, so a single hung
+    /// attempt only fails once the host gives up; the budget bounds how long retries continue.
+    fn with_retry<T>(
+        &self,
+        description: &str,
+        settings: &AdapterSettings,
+        mut operation: impl FnMut() -> Result<T, String>,
+        on_error: impl FnOnce(String) -> BinaryManagerError,
+    ) -> Result<T, BinaryManagerError> {
+        let timeout = std::time::Duration::from_secs(
+            settings.timeout_secs.unwrap_or(Self::DEFAULT_TIMEOUT_SECS),
+        );
+        let started = std::time::Instant::now();
+        let attempts = settings
+            .retry_attempts
+            .unwrap_or(Self::DEFAULT_RETRY_ATTEMPTS)
+            .max(1);
+        let mut delay = std::time::Duration::from_millis(
+            settings
+                .retry_delay_ms
+                .unwrap_or(Self::DEFAULT_RETRY_DELAY_MS),
+        );
+
+        let mut attempt = 1;
+        loop {
+            LOG.debug(&format!("{} attempt {}/{}", description, attempt, attempts));
+            let attempt_started = std::time::Instant::now();
+            let result = operation();
+            LOG.debug(&format!(
+                "{} attempt {} took {:?}",
+                description,
+                attempt,
+                attempt_started.elapsed()
+            ));
+
+            match result {
+                Ok(value) => {
+                    if started.elapsed() > timeout {
+                        LOG.warn(&format!(
+                            "{} succeeded but exceeded the {:?} timeout after {:?}",
+                            description,
+                            timeout,
+                            started.elapsed()
+                        ));
+                    }
+                    return Ok(value);
+                }
+                Err(e) if started.elapsed() >= timeout => {
+                    return Err(BinaryManagerError::Timeout {
+                        operation: description.to_string(),
+                        elapsed: started.elapsed(),
+                        message: e,
+                    });
+                }
+                Err(e) if attempt < attempts && Self::is_transient_error(&e) => {
+                    LOG.warn(&format!(
+                        "{} attempt {} failed with transient error: {}. Retrying in {:?}",
+                        description, attempt, e, delay
+                    ));
+                    std::thread::sleep(delay.min(timeout.saturating_sub(started.elapsed())));
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err(on_error(e)),
+            }
+        }
+    }
+
+    /// Whether an error message looks like a temporary network problem worth retrying
+    fn is_transient_error(message: &str) -> bool {
+        const TRANSIENT_MARKERS: &[&str] = &[
+            "timed out",
+            "timeout",
+            "connection",
+            "reset",
+            "broken pipe",
+            "temporar",
+            "unavailable",
+            "unexpected eof",
+            "dns",
+            "500",
+            "502",
+            "503",
+            "504",
+        ];
+
+        let message = message.to_ascii_lowercase();
+        TRANSIENT_MARKERS
+            .iter()
+            .any(|marker| message.contains(marker))
+    }
+
+    /// Returns the lowercase file name at the end of a download URL or asset name
+    fn asset_file_name(download_url: &str) -> String {
+        let path = download_url
+            .split(['?', '#'])
+            .next()
+            .unwrap_or(download_url);
+        path.rsplit('/').next().unwrap_or(path).to_ascii_lowercase()
+    }
+
+    /// Strips the archive or executable suffix from an asset name, e.g.
+    /// "netcoredbg-linux-x64.tar.gz" yields "netcoredbg-linux-x64"
+    fn asset_stem(asset_name: &str) -> String {
+        let name = Self::asset_file_name(asset_name);
+        Self::ASSET_SUFFIXES
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))
+            .map(str::to_string)
+            .unwrap_or(name)
+    }
+
+    /// Infers how an asset is unpacked from the file name at the end of its download URL.
+    /// File names without an extension (or ending in `.exe`) are taken to be the executable itself.
+    fn asset_format(download_url: &str) -> Result<AssetFormat, BinaryManagerError> {
+        let name = Self::asset_file_name(download_url);
+
+        if name.ends_with(".zip") {
+            Ok(AssetFormat::Archive(DownloadedFileType::Zip))
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Ok(AssetFormat::Archive(DownloadedFileType::GzipTar))
+        } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Ok(AssetFormat::TarXz)
+        } else if name.ends_with(".exe") || !name.contains('.') {
+            Ok(AssetFormat::RawExecutable)
+        } else {
+            Err(BinaryManagerError::UnsupportedAsset {
+                url: download_url.to_string(),
+            })
+        }
+    }
+
+    /// Downloads `version` into `temp_dir`, extracting it if it is an archive
+    fn download_asset(
+        &self,
+        version: &AdapterVersion,
+        format: AssetFormat,
+        temp_dir: &std::path::Path,
+        settings: &AdapterSettings,
+    ) -> Result<(), BinaryManagerError> {
+        let (target, file_type) = match format {
+            AssetFormat::Archive(file_type) => (temp_dir.to_path_buf(), file_type),
+            AssetFormat::TarXz => (
+                temp_dir.join("archive.tar.xz"),
+                DownloadedFileType::Uncompressed,
+            ),
+            AssetFormat::RawExecutable => (
+                temp_dir.join(Self::get_executable_name()),
+                DownloadedFileType::Uncompressed,
+            ),
+        };
+        let target_str = Self::to_os_full_path_str(&target)?;
+
+        self.with_retry(
+            "download",
+            settings,
+            || zed_extension_api::download_file(&version.download_url, &target_str, file_type),
+            |message| BinaryManagerError::Download {
+                url: version.download_url.clone(),
+                message,
+            },
+        )?;
+
+        if let AssetFormat::TarXz = format {
+            Self::extract_tar_xz(&target, temp_dir)?;
+            let _ = std::fs::remove_file(&target);
+        }
+
+        Ok(())
+    }
+
+    /// Unpacks a `.tar.xz` archive with the system `tar`
+    fn extract_tar_xz(
+        archive: &std::path::Path,
+        dest: &std::path::Path,
+    ) -> Result<(), BinaryManagerError> {
+        let archive = Self::to_os_full_path_str(&Self::absolute_path(archive)?)?;
+        let dest = Self::to_os_full_path_str(&Self::absolute_path(dest)?)?;
+
+        let output = Command::new("tar")
+            .args(["-xJf", &archive, "-C", &dest])
+            .output()
+            .map_err(|e| {
+                BinaryManagerError::Extraction(format!(
+                    "Failed to run tar to extract {}: {}",
+                    archive, e
+                ))
+            })?;
+
+        if output.status != Some(0) {
+            return Err(BinaryManagerError::Extraction(format!(
+                "tar exited with status {:?} extracting {}: {}",
+                output.status,
+                archive,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Builds the version information for a `download_url` override. The version directory is
+    /// named after the `version` setting when present, otherwise after a hash of the URL.
+    fn direct_download_version(
+        download_url: &str,
+        settings: &AdapterSettings,
+    ) -> Result<AdapterVersion, BinaryManagerError> {
+        Self::asset_format(download_url).map_err(|e| BinaryManagerError::InvalidSetting {
+            setting: "download_url",
+            message: e.to_string(),
+        })?;
+
+        let tag_name = settings.version.clone().unwrap_or_else(|| {
+            let mut hasher = Sha256::new();
+            hasher.update(download_url.as_bytes());
+            format!("url-{}", &hasher.finalize_hex()[..12])
+        });
+
+        Ok(AdapterVersion {
+            tag_name,
+            download_url: download_url.to_string(),
+            checksum_url: None,
+        })
+    }
+
+    /// Fails early when the install root clearly lacks the space needed to download and extract
+    /// the archive. Skipped when either the archive size or the free space cannot be determined.
+    fn ensure_disk_space(
+        root: &std::path::Path,
+        version: &AdapterVersion,
+    ) -> Result<(), BinaryManagerError> {
+        let Some(archive_size) = Self::remote_file_size(&version.download_url) else {
+            LOG.debug("Archive size unknown, skipping free disk space check");
+            return Ok(());
+        };
+        let install_root = Self::absolute_path(root)?;
+        let Some(available) = Self::free_disk_space(&install_root) else {
+            LOG.debug("Free disk space unknown, skipping free disk space check");
+            return Ok(());
+        };
+
+        let required = archive_size.saturating_mul(Self::EXTRACTION_SPACE_MULTIPLIER);
+        const MB: u64 = 1024 * 1024;
+        LOG.debug(&format!(
+            "Disk space: need ~{} MB, {} MB available in {}",
+            required / MB,
+            available / MB,
+            install_root.display()
+        ));
+
+        if available < required {
+            return Err(BinaryManagerError::InsufficientDiskSpace {
+                version: version.tag_name.clone(),
+                dir: install_root.display().to_string(),
+                required_mb: required.div_ceil(MB),
+                available_mb: available / MB,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Size of a remote file according to the `Content-Length` of a HEAD request
+    fn remote_file_size(url: &str) -> Option<u64> {
+        let response = HttpRequest::builder()
+            .method(HttpMethod::Head)
+            .url(url)
+            .header("User-Agent", "zed-netcoredbg")
+            .redirect_policy(RedirectPolicy::FollowAll)
+            .build()
+            .and_then(|request| request.fetch())
+            .ok()?;
+
+        response
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.trim().parse().ok())
+    }
+
+    /// Free space in bytes on the filesystem holding `dir`, using `df` on Unix and
+    /// `fsutil` on Windows since the extension sandbox has no direct statvfs equivalent
+    fn free_disk_space(dir: &std::path::Path) -> Option<u64> {
+        let dir = Self::to_os_full_path_str(dir).ok()?;
+
+        match platform::os() {
+            zed::Os::Windows => {
+                let drive = dir.get(..2).filter(|drive| drive.ends_with(':'))?;
+                let output = Command::new("fsutil")
+                    .args(["volume", "diskfree", drive])
+                    .output()
+                    .ok()?;
+                Self::parse_fsutil_free_space(&String::from_utf8_lossy(&output.stdout))
+            }
+            _ => {
+                let output = Command::new("df").args(["-Pk", &dir]).output().ok()?;
+                Self::parse_df_free_space(&String::from_utf8_lossy(&output.stdout))
+            }
+        }
+    }
+
+    /// Parses the "Available" column (in KiB) of POSIX `df -Pk` output
+    fn parse_df_free_space(output: &str) -> Option<u64> {
+        let available_kib: u64 = output
+            .lines()
+            .nth(1)?
+            .split_whitespace()
+            .nth(3)?
+            .parse()
+            .ok()?;
+        Some(available_kib * 1024)
+    }
+
+    /// Parses `fsutil volume diskfree` output, preferring the bytes available to the caller
+    fn parse_fsutil_free_space(output: &str) -> Option<u64> {
+        let parse_line = |line: &str| -> Option<u64> {
+            let value = line.split_once(':')?.1;
+            let value = value.split('(').next()?;
+            value
+                .chars()
+                .filter(char::is_ascii_digit)
+                .collect::<String>()
+                .parse()
+                .ok()
+        };
+
+        let lines: Vec<&str> = output
+            .lines()
+            .filter(|line| line.to_ascii_lowercase().contains("free bytes"))
+            .collect();
+        lines
+            .iter()
+            .find(|line| line.to_ascii_lowercase().contains("avail"))
+            .or_else(|| lines.first())
+            .and_then(|line| parse_line(line))
+    }
+
+    /// Resolves the expected SHA256 digest of the netcoredbg executable, preferring the
+    /// user-supplied `checksum` setting over a checksum file published with the release
+    fn expected_checksum(
+        &self,
+        version: &AdapterVersion,
+        settings: &AdapterSettings,
+    ) -> Result<Option<String>, BinaryManagerError> {
+        if let Some(checksum) = &settings.checksum {
+            return Ok(Some(checksum.trim().to_string()));
+        }
+
+        let Some(checksum_url) = &version.checksum_url else {
+            return Ok(None);
+        };
+
+        let response = HttpRequest::builder()
+            .method(HttpMethod::Get)
+            .url(checksum_url)
+            .header("User-Agent", "zed-netcoredbg")
+            .redirect_policy(RedirectPolicy::FollowAll)
+            .build()
+            .and_then(|request| request.fetch())
+            .map_err(|message| BinaryManagerError::Download {
+                url: checksum_url.clone(),
+                message,
+            })?;
+        let contents = String::from_utf8_lossy(&response.body);
+
+        // Checksum files use the `sha256sum` format: "<digest>  <file name>" per line
+        let exe_name = Self::get_executable_name();
+        let digest = contents.lines().find_map(|line| {
+            let mut parts = line.split_whitespace();
+            let digest = parts.next()?;
+            let file_name = parts.next()?.trim_start_matches('*');
+            (std::path::Path::new(file_name)
+                .file_name()
+                .is_some_and(|name| name == exe_name))
+            .then(|| digest.to_string())
+        });
+
+        if digest.is_none() {
+            LOG.warn(&format!(
+                "Checksum file {} has no entry for {}, the archive itself cannot be verified after extraction",
+                checksum_url, exe_name
+            ));
+        }
+
+        Ok(digest)
+    }
+
+    /// Verifies that the SHA256 digest of the file at `path` matches `expected` (hex, case-insensitive)
+    pub fn verify_checksum(
+        path: &std::path::Path,
+        expected: &str,
+    ) -> Result<(), BinaryManagerError> {
+        let actual = Sha256::hash_file(path).map_err(BinaryManagerError::FileSystem)?;
+        let expected = expected.trim().to_ascii_lowercase();
+
+        LOG.debug(&format!(
+            "Checksum for {}: expected {}, actual {}",
+            path.display(),
+            expected,
+            actual
+        ));
+
+        if actual != expected {
+            return Err(BinaryManagerError::ChecksumMismatch {
+                path: path.display().to_string(),
+                expected,
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Downloads and extracts the netcoredbg binary, returning the path to the executable
+    fn download_and_extract_binary(
+        &self,
+        root: &std::path::Path,
+        version: &AdapterVersion,
+        settings: &AdapterSettings,
+    ) -> Result<String, BinaryManagerError> {
+        let format = Self::asset_format(&version.download_url)?;
+        let mut progress = InstallProgress::new(&version.tag_name);
+
+        Self::ensure_install_root(root)?;
+        progress.phase("checking free disk space");
+        Self::ensure_disk_space(root, version)?;
+
+        // `download_file` only writes inside the extension's work directory, so the archive is
+        // always extracted there and then copied into the install root
+        let temp_dir = ScratchDir::create(std::path::PathBuf::from(format!(
+            "temp_netcoredbg_v{}",
+            version.tag_name
+        )))?;
+
+        progress.phase("downloading and extracting archive");
+        self.download_asset(version, format, &temp_dir.path, settings)?;
+
+        // Install into a staging directory that is only renamed once the install is complete,
+        // so an interrupted install never leaves a half-populated version directory behind
+        let version_dir = Self::version_dir(root, &version.tag_name);
+        let staging_dir = Self::staging_dir(root, &version.tag_name);
+        if staging_dir.exists() {
+            std::fs::remove_dir_all(&staging_dir).map_err(|e| {
+                BinaryManagerError::FileSystem(format!(
+                    "Failed to remove stale staging directory: {}",
+                    e
+                ))
+            })?;
+        }
+        std::fs::create_dir_all(&staging_dir).map_err(|e| {
+            BinaryManagerError::FileSystem(format!("Failed to create staging directory: {}", e))
+        })?;
+
+        progress.phase("copying extracted files");
+        self.copy_extracted_content(&temp_dir.path, &staging_dir, settings)?;
+        Self::flatten_nested_install(&staging_dir);
+
+        let exe_name = Self::get_executable_name();
+        let staged_binary_path = staging_dir.join(exe_name);
+
+        if !staged_binary_path.exists() {
+            let _ = std::fs::remove_dir_all(&staging_dir);
+            return Err(BinaryManagerError::Extraction(format!(
+                "netcoredbg executable not found at: {}",
+                staged_binary_path.display()
+            )));
+        }
+
+        drop(temp_dir);
+
+        progress.phase("verifying checksum");
+        if let Some(expected) = self.expected_checksum(version, settings)? {
+            if let Err(e) = Self::verify_checksum(&staged_binary_path, &expected) {
+                LOG.error(&format!(
+                    "Refusing to install netcoredbg {}: {}",
+                    version.tag_name, e
+                ));
+                let _ = std::fs::remove_dir_all(&staging_dir);
+                return Err(e);
+            }
+        }
+
+        progress.phase("making executable");
+        zed::make_file_executable(&Self::to_os_full_path_str(&staged_binary_path)?).map_err(
+            |e| BinaryManagerError::Extraction(format!("Failed to make file executable: {}", e)),
+        )?;
+
+        progress.phase("moving into place");
+        if version_dir.exists() {
+            LOG.debug(&format!(
+                "Replacing incomplete install at {}",
+                version_dir.display()
+            ));
+            std::fs::remove_dir_all(&version_dir).map_err(|e| {
+                BinaryManagerError::FileSystem(format!(
+                    "Failed to remove incomplete install: {}",
+                    e
+                ))
+            })?;
+        }
+        std::fs::rename(&staging_dir, &version_dir).map_err(|e| {
+            BinaryManagerError::FileSystem(format!(
+                "Failed to move {} into place at {}: {}",
+                staging_dir.display(),
+                version_dir.display(),
+                e
+            ))
+        })?;
+
+        let binary_path_str = Self::to_os_full_path_str(&version_dir.join(exe_name))?;
+
+        Ok(binary_path_str)
+    }
+
+    /// Copies extracted content from temp_dir into version_dir, handling nested directory structure.
+    /// Only the files netcoredbg needs are copied unless `copy_all_files` is set or the layout
+    /// is unrecognized.
+    fn copy_extracted_content(
+        &self,
+        temp_dir: &std::path::Path,
+        version_dir: &std::path::Path,
+        settings: &AdapterSettings,
+    ) -> Result<(), BinaryManagerError> {
+        let exe_name = Self::get_executable_name();
+
+        let binary_source_path = self.find_binary_in_extracted_content(temp_dir, exe_name)?;
+
+        let source_dir = binary_source_path.parent().ok_or_else(|| {
+            BinaryManagerError::Extraction("Binary has no parent directory".to_string())
+        })?;
+
+        LOG.debug(&format!(
+            "Found binary at: {}, copying from: {}",
+            binary_source_path.display(),
+            source_dir.display()
+        ));
+
+        let copy_error = |e: String| {
+            BinaryManagerError::Extraction(format!(
+                "Failed to copy extracted content from {}: {}",
+                source_dir.display(),
+                e
+            ))
+        };
+
+        let needed_files = Self::collect_needed_files(source_dir, source_dir, exe_name)
+            .map_err(|e| copy_error(e.to_string()))?;
+        // A layout with nothing next to the executable is unknown, so nothing is left out
+        let recognized = needed_files
+            .iter()
+            .any(|path| path.file_name().is_some_and(|name| name != exe_name));
+
+        // Windows fails copies past MAX_PATH with an unhelpful OS error, so name the cause
+        let long_path_note = match platform::os() {
+            zed::Os::Windows => needed_files
+                .iter()
+                .map(|relative| {
+                    normalize_windows_path(&version_dir.join(relative).to_string_lossy())
+                })
+                .find(|target| target.len() >= WINDOWS_MAX_PATH)
+                .map(|target| {
+                    format!(
+                        ". {} is {} characters long, more than Windows allows; set 'install_dir' to a shorter path",
+                        target,
+                        target.len()
+                    )
+                }),
+            _ => None,
+        }
+        .unwrap_or_default();
+        let copy_failed = |e: String| copy_error(format!("{}{}", e, long_path_note));
+
+        let bytes_copied = if settings.copy_all_files.unwrap_or(false) || !recognized {
+            if !recognized {
+                LOG.info("Unrecognized archive layout, copying all extracted files");
+            }
+            let copy_options = dir::CopyOptions::new().content_only(true);
+            dir::copy(source_dir, version_dir, &copy_options)
+                .map_err(|e| copy_failed(e.to_string()))?
+        } else {
+            let mut bytes_copied = 0;
+            for relative_path in &needed_files {
+                let target = version_dir.join(relative_path);
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| copy_failed(e.to_string()))?;
+                }
+                bytes_copied += std::fs::copy(source_dir.join(relative_path), &target)
+                    .map_err(|e| copy_failed(e.to_string()))?;
+            }
+            bytes_copied
+        };
+
+        LOG.debug(&format!(
+            "Copied {} bytes into {}",
+            bytes_copied,
+            version_dir.display()
+        ));
+
+        Ok(())
+    }
+
+    /// Lists the files below `dir` (relative to `base`) that netcoredbg needs at runtime:
+    /// the executable, native and managed libraries, and license files
+    fn collect_needed_files(
+        base: &std::path::Path,
+        dir: &std::path::Path,
+        exe_name: &str,
+    ) -> std::io::Result<Vec<std::path::PathBuf>> {
+        let mut files = Vec::new();
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                files.extend(Self::collect_needed_files(base, &path, exe_name)?);
+                continue;
+            }
+
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_ascii_lowercase())
+                .unwrap_or_default();
+            let is_needed = name == exe_name.to_ascii_lowercase()
+                || name.ends_with(".dll")
+                || name.ends_with(".so")
+                || name.contains(".so.")
+                || name.ends_with(".dylib")
+                || name.contains("license")
+                || name.contains("notice");
+            if is_needed {
+                if let Ok(relative_path) = path.strip_prefix(base) {
+                    files.push(relative_path.to_path_buf());
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Moves the contents of a lone subdirectory holding the executable up into `dir`, healing
+    /// installs of archives nested one directory deep (`netcoredbg_v{tag}/netcoredbg/netcoredbg`).
+    /// Failures are logged, leaving the layout as it was.
+    fn flatten_nested_install(dir: &std::path::Path) {
+        let exe_name = Self::get_executable_name();
+        if dir.join(exe_name).is_file() {
+            return;
+        }
+
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let entries: Vec<std::path::PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect();
+        let [nested] = entries.as_slice() else {
+            return;
+        };
+        if !nested.is_dir() || !nested.join(exe_name).is_file() {
+            return;
+        }
+
+        LOG.info(&format!(
+            "Flattening nested install layout {}",
+            nested.display()
+        ));
+
+        // The nested directory is usually named like the executable, so it is moved out of the
+        // way before its contents are moved up
+        let moved_nested = dir.join(".nested");
+        let result = std::fs::rename(nested, &moved_nested).and_then(|()| {
+            for entry in std::fs::read_dir(&moved_nested)? {
+                let entry = entry?;
+                std::fs::rename(entry.path(), dir.join(entry.file_name()))?;
+            }
+            std::fs::remove_dir(&moved_nested)
+        });
+        if let Err(e) = result {
+            LOG.warn(&format!(
+                "Failed to flatten nested install layout in {}: {}",
+                dir.display(),
+                e
+            ));
+        }
+    }
+
+    /// Recursively searches for the netcoredbg binary in the extracted content
+    fn find_binary_in_extracted_content(
+        &self,
+        search_dir: &std::path::Path,
+        exe_name: &str,
+    ) -> Result<std::path::PathBuf, BinaryManagerError> {
+        fn find_binary_recursive(
+            dir: &std::path::Path,
+            exe_name: &str,
+        ) -> Result<Option<std::path::PathBuf>, String> {
+            let entries = std::fs::read_dir(dir)
+                .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+
+            for entry in entries {
+                let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+                let path = entry.path();
+
+                if path.is_file() && path.file_name().is_some_and(|name| name == exe_name) {
+                    return Ok(Some(path));
+                } else if path.is_dir() {
+                    if let Some(found) = find_binary_recursive(&path, exe_name)? {
+                        return Ok(Some(found));
+                    }
+                }
+            }
+            Ok(None)
+        }
+
+        find_binary_recursive(search_dir, exe_name)
+            .map_err(BinaryManagerError::Extraction)?
+            .ok_or_else(|| {
+                BinaryManagerError::Extraction(format!(
+                    "Could not find {} binary in extracted content at {}",
+                    exe_name,
+                    search_dir.display()
+                ))
+            })
+    }
+
+    /// Gets the netcoredbg binary path, downloading if necessary
+    pub fn get_binary_path(
+        &self,
+        user_provided_path: Option<String>,
+        settings: &AdapterSettings,
+        worktree: &Worktree,
+    ) -> Result<String, BinaryManagerError> {
+        LOG.debug("Starting get_binary_path");
+        let settings = &Self::with_env_token(settings, worktree);
+
+        // Priority 1: User-provided path, with `~` and environment variables expanded. A path in
+        // the project's debug configuration wins over the one from the Zed settings.
+        let user_path = settings
+            .binary
+            .clone()
+            .map(|path| (path, PathScope::DebugConfiguration))
+            .or_else(|| user_provided_path.map(|path| (path, PathScope::ZedSettings)));
+        if let Some((user_path, scope)) = user_path {
+            let env: HashMap<String, String> = worktree.shell_env().into_iter().collect();
+            let expanded_path = expand_path(&user_path, &env).map_err(|message| {
+                BinaryManagerError::UserPathInvalid {
+                    scope,
+                    path: user_path.clone(),
+                    message,
+                }
+            })?;
+            let resolved_path =
+                Self::resolve_user_path(&expanded_path, &user_path, scope, worktree)?;
+            LOG.info(&format!(
+                "Using user-provided path from {}: {} (expanded from {})",
+                scope, resolved_path, user_path
+            ));
+            if std::path::Path::new(&resolved_path).is_dir() {
+                return Self::find_binary_in_user_dir(std::path::Path::new(&resolved_path), scope);
+            }
+            return Ok(resolved_path);
+        }
+
+        // Priority 2: netcoredbg installed on PATH, e.g. by a system package manager
+        if settings.prefer_system_binary.unwrap_or(true) {
+            if let Some(system_path) = worktree.which(Self::get_executable_name()) {
+                match self.validate_binary(&system_path) {
+                    Ok(()) => {
+                        LOG.info(&format!("Using netcoredbg from PATH: {}", system_path));
+                        return Ok(system_path);
+                    }
+                    Err(e) => LOG.warn(&format!(
+                        "Ignoring netcoredbg found on PATH at {}: {}",
+                        system_path, e
+                    )),
+                }
+            }
+        }
+
+        // Without a release asset for this platform there is nothing to download or install
+        match Self::platform_support(settings) {
+            PlatformSupport::UserPathRequired { reason } if settings.download_url.is_none() => {
+                return Err(BinaryManagerError::UnsupportedPlatform { reason });
+            }
+            PlatformSupport::Supported {
+                warning: Some(warning),
+                ..
+            } => LOG.warn(warning),
+            _ => {}
+        }
+
+        if settings.force_reinstall.unwrap_or(false)
+            && !self.force_reinstall_done.swap(true, Ordering::SeqCst)
+        {
+            return self.reinstall(settings, worktree);
+        }
+
+        let pinned_version = settings.version.as_deref();
+        let root = self.recorded_root(Self::install_root(settings, worktree)?);
+        LOG.debug(&format!("Install root: {}", root.display()));
+        Self::remove_partial_installs(&root);
+        if Self::check_first_launch(&root) {
+            self.set_cached_path(None);
+        }
+
+        // Priority 3: Check the in-memory cache, then the manifest persisted by an earlier session
+        if let Some(path_str) = self
+            .cached_path()
+            .is_none()
+            .then(|| self.read_manifest(&root, settings))
+            .flatten()
+        {
+            LOG.debug(&format!("Using binary recorded in manifest: {}", path_str));
+            self.set_cached_path(Some(path_str.clone()));
+            self.notify_update(&root, settings);
+            return Ok(path_str);
+        }
+        if let Some(cached_path) = self.cached_path() {
+            let cached = std::path::Path::new(&cached_path);
+            let matches_settings = cached.starts_with(&root)
+                && pinned_version.is_none_or(|tag| {
+                    cached.parent() == Some(Self::version_dir(&root, tag).as_path())
+                });
+            if !matches_settings {
+                LOG.debug(&format!(
+                    "Cached binary {} does not match the pinned version or install root, ignoring cache",
+                    cached_path
+                ));
+            } else if self.is_intact_install(cached, settings) {
+                LOG.debug(&format!("Using cached binary path: {}", cached_path));
+                self.notify_update(&root, settings);
+                return Ok(cached_path);
+            } else {
+                LOG.debug("Cached binary no longer exists or is broken, will re-download");
+                self.set_cached_path(None);
+            }
+        }
+
+        let exe_name = Self::get_executable_name();
+
+        // Priority 4: A pinned version already on disk can be reused without contacting GitHub
+        if let Some(tag) = pinned_version {
+            Self::flatten_nested_install(&Self::version_dir(&root, tag));
+            let pinned_binary_path =
+                Self::absolute_path(&Self::version_dir(&root, tag).join(exe_name))?;
+            if self.is_intact_install(&pinned_binary_path, settings) {
+                LOG.debug(&format!(
+                    "Found pinned version {} on disk: {}",
+                    tag,
+                    pinned_binary_path.display()
+                ));
+                let path_str = Self::to_os_full_path_str(&pinned_binary_path)?;
+                self.remember(&root, tag, &path_str, settings);
+                self.notify_update(&root, settings);
+                return Ok(path_str);
+            }
+        }
+
+        // Priority 5: The newest local install, probed before touching the network. A pinned
+        // version or download URL must not silently be replaced by a different local copy.
+        let auto_update = settings.auto_update.unwrap_or_default();
+        let newest_installed = (pinned_version.is_none() && settings.download_url.is_none())
+            .then(|| self.find_newest_installed(&root, settings))
+            .flatten();
+        if let (AutoUpdate::Never, Some(installed)) = (auto_update, &newest_installed) {
+            LOG.info(&format!(
+                "auto_update is 'never', using installed version {}",
+                installed.tag
+            ));
+            return self.use_installed(&root, installed, settings);
+        }
+
+        // A local copy is used without contacting GitHub until its last update check is older
+        // than the release cache TTL
+        if let Some(installed) = &newest_installed {
+            if Self::can_skip_release_lookup(&root, settings, installed) {
+                LOG.debug(&format!(
+                    "Releases were checked within the cache TTL, using installed version {}",
+                    installed.tag
+                ));
+                return self.use_installed(&root, installed, settings);
+            }
+        }
+
+        // With a usable local copy the update check is a single attempt: the extension cannot
+        // run it in the background, and retrying would only delay a session that can start anyway
+        let lookup_settings = match &newest_installed {
+            Some(_) => AdapterSettings {
+                retry_attempts: Some(1),
+                ..settings.clone()
+            },
+            None => settings.clone(),
+        };
+
+        // Priority 6: Resolve the version to install and check for it on disk before downloading
+        let version = match self.resolve_version(&root, &lookup_settings) {
+            Ok(version) => version,
+            Err(e) => {
+                let Some(installed) = &newest_installed else {
+                    return Err(e);
+                };
+                LOG.warn(&format!(
+                    "Failed to fetch release info ({}), falling back to offline copy: {}",
+                    e,
+                    installed.path.display()
+                ));
+                return self.use_installed(&root, installed, settings);
+            }
+        };
+        LOG.debug(&format!("Found version: {}", version.tag_name));
+
+        if let Some(installed) = &newest_installed {
+            match compare_versions(&version.tag_name, &installed.tag) {
+                std::cmp::Ordering::Greater if Self::is_bad_version(&root, &version.tag_name) => {
+                    LOG.warn(&format!(
+                        "Skipping netcoredbg {}, which failed validation after an earlier update, keeping installed version {}. Set 'force_reinstall' to retry it.",
+                        version.tag_name, installed.tag
+                    ));
+                    return self.use_installed(&root, installed, settings);
+                }
+                std::cmp::Ordering::Greater if auto_update == AutoUpdate::Prompt => {
+                    LOG.info(&format!(
+                        "netcoredbg {} is available, keeping installed version {}. Set 'auto_update' to 'always' to install updates automatically.",
+                        version.tag_name, installed.tag
+                    ));
+                    return self.use_installed(&root, installed, settings);
+                }
+                std::cmp::Ordering::Greater => LOG.info(&format!(
+                    "Updating netcoredbg from {} to {}",
+                    installed.tag, version.tag_name
+                )),
+                // The local install is at least as new as the release, e.g. an earlier pinned install
+                _ => {
+                    LOG.debug(&format!(
+                        "Installed version {} is up to date (latest release: {})",
+                        installed.tag, version.tag_name
+                    ));
+                    return self.use_installed(&root, installed, settings);
+                }
+            }
+        }
+
+        let version_dir = Self::version_dir(&root, &version.tag_name);
+        Self::flatten_nested_install(&version_dir);
+        let existing_binary_path = Self::absolute_path(&version_dir.join(exe_name))?;
+
+        if self.is_intact_install(&existing_binary_path, settings) {
+            LOG.debug(&format!(
+                "Found existing binary on disk: {}",
+                existing_binary_path.display()
+            ));
+            // Convert PathBuf -> String safely for caching
+            let path_str = Self::to_os_full_path_str(&existing_binary_path)?;
+            self.remember(&root, &version.tag_name, &path_str, settings);
+            return Ok(path_str);
+        }
+
+        // Another instance may be installing the same version; wait for it, then re-check
+        let root = self.writable_root(&root, worktree)?;
+        let existing_binary_path =
+            Self::absolute_path(&Self::version_dir(&root, &version.tag_name).join(exe_name))?;
+        let _install_lock = InstallLock::acquire(&Self::lock_path(&root, &version.tag_name))?;
+        if self.is_intact_install(&existing_binary_path, settings) {
+            LOG.debug(&format!(
+                "Binary was installed by another instance while waiting: {}",
+                existing_binary_path.display()
+            ));
+            let path_str = Self::to_os_full_path_str(&existing_binary_path)?;
+            self.remember(&root, &version.tag_name, &path_str, settings);
+            return Ok(path_str);
+        }
+
+        // Priority 7: Download and extract the release archive
+        LOG.info(&format!(
+            "No existing binary found, downloading from {}",
+            version.download_url
+        ));
+        match self.install(&root, &version, settings) {
+            // Roll back to the previous install when an update turns out not to run here
+            Err(e @ BinaryManagerError::VerificationFailed { .. }) => {
+                let Some(installed) = &newest_installed else {
+                    return Err(e);
+                };
+                LOG.warn(&format!(
+                    "{}. Rolling back to netcoredbg {}; {} is skipped by automatic updates until a newer release is published or 'force_reinstall' is set.",
+                    e, installed.tag, version.tag_name
+                ));
+                let path_str = self.use_installed(&root, installed, settings)?;
+                Self::set_bad_version(&root, &version.tag_name, true);
+                Ok(path_str)
+            }
+            // An update is only kept once its first session showed that it starts
+            Ok(path_str) if newest_installed.is_some() => {
+                Self::expect_first_launch(&root, &version.tag_name);
+                Ok(path_str)
+            }
+            result => result,
+        }
+    }
+
+    /// Removes the install of the version the settings resolve to and downloads it again,
+    /// repairing installs whose files were corrupted or quarantined after installation
+    pub fn reinstall(
+        &self,
+        settings: &AdapterSettings,
+        worktree: &Worktree,
+    ) -> Result<String, BinaryManagerError> {
+        let settings = &Self::with_env_token(settings, worktree);
+        let root = self.recorded_root(Self::install_root(settings, worktree)?);
+        Self::remove_partial_installs(&root);
+        let version = self.resolve_version(&root, settings)?;
+        LOG.info(&format!("Reinstalling netcoredbg {}", version.tag_name));
+        Self::set_bad_version(&root, &version.tag_name, false);
+
+        self.set_cached_path(None);
+        let root = self.writable_root(&root, worktree)?;
+        let _install_lock = InstallLock::acquire(&Self::lock_path(&root, &version.tag_name))?;
+
+        let version_dir = Self::version_dir(&root, &version.tag_name);
+        if version_dir.exists() {
+            std::fs::remove_dir_all(&version_dir).map_err(|e| {
+                BinaryManagerError::FileSystem(format!(
+                    "Failed to remove {} for reinstall: {}",
+                    version_dir.display(),
+                    e
+                ))
+            })?;
+        }
+
+        self.install(&root, &version, settings)
+    }
+
+    /// Resolves the version to install: the `download_url` override, or the pinned or latest
+    /// GitHub release
+    fn resolve_version(
+        &self,
+        root: &std::path::Path,
+        settings: &AdapterSettings,
+    ) -> Result<AdapterVersion, BinaryManagerError> {
+        if let Some(download_url) = settings.download_url.as_deref() {
+            LOG.info(&format!("Using download URL override: {}", download_url));
+            return Self::direct_download_version(download_url, settings);
+        }
+
+        let pinned_version = settings.version.as_deref();
+        match pinned_version {
+            Some(tag) => LOG.debug(&format!("Fetching pinned release {} from GitHub", tag)),
+            None => {
+                LOG.debug("Fetching latest release info from GitHub to check for existing binary")
+            }
+        }
+        let resolve_started = std::time::Instant::now();
+        let release = self.fetch_release(root, pinned_version, settings);
+        LOG.debug(&format!(
+            "Resolving release took {:?}",
+            resolve_started.elapsed()
+        ));
+        release
+    }
+
+    /// Installs `version` into the install root, caches the resulting binary path and removes
+    /// stale versions. The caller holds the install lock.
+    fn install(
+        &self,
+        root: &std::path::Path,
+        version: &AdapterVersion,
+        settings: &AdapterSettings,
+    ) -> Result<String, BinaryManagerError> {
+        let binary_path = self.install_and_verify(root, version, settings)?;
+
+        self.remember(root, &version.tag_name, &binary_path, settings);
+
+        self.cleanup_old_versions(
+            root,
+            settings
+                .keep_versions
+                .unwrap_or(Self::DEFAULT_KEEP_VERSIONS),
+            &binary_path,
+        );
+
+        Ok(binary_path)
+    }
+
+    /// Lists the netcoredbg versions installed in the install root the given settings resolve to
+    pub fn list_installed_versions(
+        &self,
+        settings: &AdapterSettings,
+        worktree: &Worktree,
+    ) -> Vec<InstalledVersion> {
+        match Self::install_root(settings, worktree) {
+            Ok(root) => Self::installed_versions(&root),
+            Err(e) => {
+                LOG.warn(&format!("Cannot list installed versions: {}", e));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Scans `root` for completed `netcoredbg_v*` directories that contain the platform
+    /// executable, newest version (by version ordering) first
+    fn installed_versions(root: &std::path::Path) -> Vec<InstalledVersion> {
+        let exe_name = Self::get_executable_name();
+
+        let Ok(entries) = std::fs::read_dir(root) else {
+            return Vec::new();
+        };
+
+        let mut installed: Vec<InstalledVersion> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if !Self::is_version_dir_name(&name) {
+                    return None;
+                }
+                let dir_metadata = entry.metadata().ok().filter(|m| m.is_dir())?;
+                Self::flatten_nested_install(&entry.path());
+                let binary_path = Self::absolute_path(&entry.path().join(exe_name)).ok()?;
+                let binary_metadata = std::fs::metadata(&binary_path)
+                    .ok()
+                    .filter(|m| m.is_file() && m.len() >= Self::MIN_BINARY_SIZE)?;
+                Some(InstalledVersion {
+                    tag: name.strip_prefix(Self::VERSION_DIR_PREFIX)?.to_string(),
+                    path: binary_path,
+                    size: binary_metadata.len(),
+                    installed_at: dir_metadata.modified().ok()?,
+                })
+            })
+            .collect();
+
+        installed.sort_by(|a, b| compare_versions(&b.tag, &a.tag));
+        installed
+    }
+
+    /// Returns the newest intact installed version. Pre-release versions are only considered
+    /// when `allow_prerelease` is set, and versions marked bad are skipped.
+    fn find_newest_installed(
+        &self,
+        root: &std::path::Path,
+        settings: &AdapterSettings,
+    ) -> Option<InstalledVersion> {
+        let allow_prerelease = settings.allow_prerelease.unwrap_or(false);
+        let bad_versions = Self::load_manifest(root)
+            .map(|manifest| manifest.bad_versions)
+            .unwrap_or_default();
+
+        let newest = Self::installed_versions(root)
+            .into_iter()
+            .find(|installed| {
+                (allow_prerelease || !is_prerelease(&installed.tag))
+                    && !bad_versions.contains(&installed.tag)
+                    && self.is_intact_install(&installed.path, settings)
+            })?;
+        LOG.debug(&format!(
+            "Newest installed version on disk: {} ({} bytes)",
+            newest.tag, newest.size
+        ));
+        Some(newest)
+    }
+
+    /// Caches `binary_path` as the binary to use and records it in the manifest
+    fn remember(
+        &self,
+        root: &std::path::Path,
+        tag: &str,
+        binary_path: &str,
+        settings: &AdapterSettings,
+    ) {
+        self.set_cached_path(Some(binary_path.to_string()));
+        Self::write_manifest(root, tag, binary_path, settings);
+    }
+
+    /// Compares an install against the digests the manifest recorded for it, so that files
+    /// mangled by antivirus software, disk corruption or manual edits are detected. Only the
+    /// executable is hashed unless `verify_full_install` is set, and installs the manifest
+    /// does not record are not checked.
+    fn verify_integrity(
+        binary_path: &std::path::Path,
+        settings: &AdapterSettings,
+    ) -> Result<(), String> {
+        let Some((version_dir, root)) = binary_path
+            .parent()
+            .and_then(|dir| Some((dir, dir.parent()?)))
+        else {
+            return Ok(());
+        };
+        let Some(mut manifest) = Self::load_manifest(root)
+            .filter(|manifest| std::path::Path::new(&manifest.binary_path) == binary_path)
+        else {
+            return Ok(());
+        };
+
+        let checksum = Sha256::hash_file(binary_path)?;
+        if checksum != manifest.checksum {
+            return Err(format!(
+                "executable SHA256 is {} but {} was recorded at install",
+                checksum, manifest.checksum
+            ));
+        }
+        if !settings.verify_full_install.unwrap_or(false) {
+            return Ok(());
+        }
+
+        let install_checksum = Sha256::hash_dir(version_dir)?;
+        match &manifest.install_checksum {
+            Some(recorded) if *recorded != install_checksum => Err(format!(
+                "install digest is {} but {} was recorded",
+                install_checksum, recorded
+            )),
+            Some(_) => Ok(()),
+            // Installs recorded before the setting was enabled start being checked from now on
+            None => {
+                manifest.install_checksum = Some(install_checksum);
+                if let Err(e) = Self::store_manifest(root, &manifest) {
+                    LOG.warn(&format!("Failed to record install digest: {}", e));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the binary recorded in the manifest if it still matches the settings and is
+    /// intact. Missing, corrupt or stale manifests are ignored and rewritten on the next
+    /// successful resolution.
+    fn read_manifest(&self, root: &std::path::Path, settings: &AdapterSettings) -> Option<String> {
+        let manifest = Self::load_manifest(root)?;
+
+        let expected_version = match (&settings.download_url, &settings.version) {
+            (Some(download_url), _) => Some(
+                Self::direct_download_version(download_url, settings)
+                    .ok()?
+                    .tag_name,
+            ),
+            (None, Some(version)) => Some(version.clone()),
+            (None, None) => None,
+        };
+        let stale = match &expected_version {
+            Some(expected) => *expected != manifest.version,
+            None if manifest.bad_versions.contains(&manifest.version) => true,
+            // An unpinned install is only reused without an update check while the latest
+            // release lookup would still be served from the release cache
+            None => {
+                settings.auto_update.unwrap_or_default() != AutoUpdate::Never
+                    && Self::unix_timestamp().saturating_sub(manifest.resolved_at)
+                        >= Self::release_cache_ttl_secs(settings)
+            }
+        };
+        if stale {
+            LOG.debug(&format!(
+                "Manifest entry {} does not match the current settings or is due for an update check",
+                manifest.version
+            ));
+            return None;
+        }
+
+        let binary_path = std::path::Path::new(&manifest.binary_path);
+        if !binary_path.starts_with(root) || !binary_path.is_file() {
+            LOG.debug(&format!(
+                "Manifest binary {} is missing or outside the install root",
+                manifest.binary_path
+            ));
+            return None;
+        }
+        self.is_intact_install(binary_path, settings)
+            .then_some(manifest.binary_path)
+    }
+
+    fn load_manifest(root: &std::path::Path) -> Option<InstallManifest> {
+        let contents = std::fs::read_to_string(root.join(Self::MANIFEST_FILE)).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                LOG.warn(&format!("Ignoring corrupt manifest: {}", e));
+                None
+            }
+        }
+    }
+
+    fn store_manifest(root: &std::path::Path, manifest: &InstallManifest) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+        std::fs::write(root.join(Self::MANIFEST_FILE), json).map_err(|e| e.to_string())
+    }
+
+    fn write_manifest(
+        root: &std::path::Path,
+        tag: &str,
+        binary_path: &str,
+        settings: &AdapterSettings,
+    ) {
+        let path = std::path::Path::new(binary_path);
+        let previous = Self::load_manifest(root);
+        let result = Sha256::hash_file(path).and_then(|checksum| {
+            let installed_at = path
+                .parent()
+                .and_then(|dir| std::fs::metadata(dir).and_then(|m| m.modified()).ok())
+                .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+                .map_or(0, |age| age.as_secs());
+            let manifest = InstallManifest {
+                binary_path: binary_path.to_string(),
+                version: tag.to_string(),
+                installed_at,
+                resolved_at: Self::unix_timestamp(),
+                checksum,
+                install_checksum: match (settings.verify_full_install, path.parent()) {
+                    (Some(true), Some(dir)) => Some(Sha256::hash_dir(dir)?),
+                    _ => None,
+                },
+                update_checked_at: previous
+                    .as_ref()
+                    .map_or(0, |manifest| manifest.update_checked_at),
+                first_launch: previous
+                    .as_ref()
+                    .and_then(|manifest| manifest.first_launch.clone()),
+                bad_versions: previous
+                    .map(|manifest| manifest.bad_versions)
+                    .unwrap_or_default(),
+            };
+            Self::store_manifest(root, &manifest)
+        });
+        if let Err(e) = result {
+            LOG.warn(&format!("Failed to write manifest: {}", e));
+        }
+    }
+
+    /// Whether a fresh install of `tag` failed validation before
+    fn is_bad_version(root: &std::path::Path, tag: &str) -> bool {
+        Self::load_manifest(root)
+            .is_some_and(|manifest| manifest.bad_versions.iter().any(|bad| bad == tag))
+    }
+
+    /// Marks `tag` as bad in the manifest, or clears the mark
+    fn set_bad_version(root: &std::path::Path, tag: &str, bad: bool) {
+        let Some(mut manifest) = Self::load_manifest(root) else {
+            return;
+        };
+        let marked = manifest.bad_versions.iter().any(|existing| existing == tag);
+        if marked == bad {
+            return;
+        }
+        if bad {
+            manifest.bad_versions.push(tag.to_string());
+        } else {
+            manifest.bad_versions.retain(|existing| existing != tag);
+        }
+        if let Err(e) = Self::store_manifest(root, &manifest) {
+            LOG.warn(&format!("Failed to update bad versions in manifest: {}", e));
+        }
+    }
+
+    /// Records that the next launch of the freshly installed `tag` is its first
+    fn expect_first_launch(root: &std::path::Path, tag: &str) {
+        let Some(mut manifest) = Self::load_manifest(root) else {
+            return;
+        };
+        manifest.first_launch = Some(FirstLaunch {
+            version: tag.to_string(),
+            engine_log: None,
+            launched_at: 0,
+        });
+        if let Err(e) = Self::store_manifest(root, &manifest) {
+            LOG.warn(&format!("Failed to record pending first launch: {}", e));
+        }
+    }
+
+    /// The engine log to launch `binary_path` with when this is the first launch of an update,
+    /// so that the next session can tell whether it started
+    pub fn first_launch_log(binary_path: &str) -> Option<std::path::PathBuf> {
+        let (root, tag) = Self::install_of(binary_path)?;
+        let manifest = Self::load_manifest(&root)?;
+        let first_launch = manifest.first_launch?;
+        (first_launch.version == tag).then(|| root.join(Self::FIRST_LAUNCH_LOG_FILE))
+    }
+
+    /// Records that the first launch of the update at `binary_path` writes `engine_log`
+    pub fn record_first_launch(binary_path: &str, engine_log: &str) {
+        let Some((root, tag)) = Self::install_of(binary_path) else {
+            return;
+        };
+        let Some(mut manifest) = Self::load_manifest(&root) else {
+            return;
+        };
+        let Some(first_launch) = manifest
+            .first_launch
+            .as_mut()
+            .filter(|first_launch| first_launch.version == tag)
+        else {
+            return;
+        };
+        first_launch.engine_log = Some(engine_log.to_string());
+        first_launch.launched_at = Self::unix_timestamp();
+        LOG.info(&format!(
+            "First launch of netcoredbg {}, checked through its engine log {}",
+            tag, engine_log
+        ));
+        if let Err(e) = Self::store_manifest(&root, &manifest) {
+            LOG.warn(&format!("Failed to record first launch: {}", e));
+        }
+    }
+
+    /// The install root and release tag of an install's executable
+    fn install_of(binary_path: &str) -> Option<(std::path::PathBuf, String)> {
+        let version_dir = std::path::Path::new(binary_path).parent()?;
+        let tag = version_dir
+            .file_name()?
+            .to_str()?
+            .strip_prefix(Self::VERSION_DIR_PREFIX)?
+            .to_string();
+        Some((version_dir.parent()?.to_path_buf(), tag))
+    }
+
+    /// Settles the first launch of an update: kept when it wrote its engine log, and otherwise
+    /// marked bad so that the previous install is used again. Returns whether it was rolled back.
+    fn check_first_launch(root: &std::path::Path) -> bool {
+        let Some(mut manifest) = Self::load_manifest(root) else {
+            return false;
+        };
+        let Some(FirstLaunch {
+            version,
+            engine_log: Some(engine_log),
+            launched_at,
+        }) = manifest.first_launch.clone()
+        else {
+            return false;
+        };
+        let written = std::fs::metadata(&engine_log).is_ok_and(|metadata| {
+            metadata.len() > 0
+                && metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+                    .is_some_and(|modified| modified.as_secs() >= launched_at)
+        });
+        manifest.first_launch = None;
+        if written {
+            LOG.debug(&format!(
+                "netcoredbg {} started on its first launch",
+                version
+            ));
+        } else {
+            LOG.error(&format!(
+                "netcoredbg {} exited immediately on its first launch (its engine log {} was never written). Rolling back to the previously installed version; {} is skipped by automatic updates until a newer release is published or 'force_reinstall' is set.",
+                version, engine_log, version
+            ));
+            if !manifest.bad_versions.contains(&version) {
+                manifest.bad_versions.push(version);
+            }
+        }
+        if let Err(e) = Self::store_manifest(root, &manifest) {
+            LOG.warn(&format!("Failed to record first launch result: {}", e));
+        }
+        !written
+    }
+
+    /// Logs a notice when a release newer than the install recorded in the manifest exists.
+    /// The check runs at most once per day, is served from the release cache while it is fresh,
+    /// and never fails the debug session.
+    fn notify_update(&self, root: &std::path::Path, settings: &AdapterSettings) {
+        if settings.download_url.is_some() || settings.auto_update == Some(AutoUpdate::Never) {
+            return;
+        }
+        let Some(mut manifest) = Self::load_manifest(root) else {
+            return;
+        };
+        let now = Self::unix_timestamp();
+        if now.saturating_sub(manifest.update_checked_at) < Self::UPDATE_NOTICE_INTERVAL_SECS {
+            return;
+        }
+        let tag = manifest.version.as_str();
+
+        // A single attempt, since retries would only delay a session that can start anyway
+        let lookup_settings = AdapterSettings {
+            version: None,
+            retry_attempts: Some(1),
+            ..settings.clone()
+        };
+        match self.fetch_release(root, None, &lookup_settings) {
+            Ok(latest) if compare_versions(&latest.tag_name, tag).is_gt() => {
+                let hint = match settings.version {
+                    Some(_) => "update or remove the 'version' pin to upgrade",
+                    None => "set auto_update or force_reinstall to upgrade",
+                };
+                LOG.warn(&format!(
+                    "netcoredbg {} is available (installed: {}); {}",
+                    latest.tag_name, tag, hint
+                ));
+            }
+            Ok(latest) => LOG.debug(&format!(
+                "Installed netcoredbg {} is up to date (latest release: {})",
+                tag, latest.tag_name
+            )),
+            Err(e) => LOG.debug(&format!("Update check failed: {}", e)),
+        }
+
+        manifest.update_checked_at = now;
+        if let Err(e) = Self::store_manifest(root, &manifest) {
+            LOG.warn(&format!("Failed to record update check: {}", e));
+        }
+    }
+
+    /// Validates an installed version and caches it as the binary to use
+    fn use_installed(
+        &self,
+        root: &std::path::Path,
+        installed: &InstalledVersion,
+        settings: &AdapterSettings,
+    ) -> Result<String, BinaryManagerError> {
+        let path_str = Self::to_os_full_path_str(&installed.path)?;
+        self.validate_binary(&path_str)?;
+        self.remember(root, &installed.tag, &path_str, settings);
+        Ok(path_str)
+    }
+
+    /// Removes all but the `keep` most recently installed `netcoredbg_v*` directories.
+    /// The directories holding `current_binary` and the cached binary are never removed,
+    /// and failures are logged rather than returned so they never fail a debug session.
+    fn cleanup_old_versions(&self, root: &std::path::Path, keep: usize, current_binary: &str) {
+        let protected_dirs: Vec<std::path::PathBuf> =
+            [Some(current_binary.to_string()), self.cached_path()]
+                .into_iter()
+                .flatten()
+                .filter_map(|binary| {
+                    std::path::Path::new(&binary)
+                        .parent()
+                        .map(std::path::Path::to_path_buf)
+                })
+                .filter_map(|dir| Self::absolute_path(&dir).ok())
+                .collect();
+
+        let mut installed = Self::installed_versions(root);
+
+        // Newest first, so everything past `keep` is stale
+        installed.sort_by_key(|installed| std::cmp::Reverse(installed.installed_at));
+
+        for dir in installed
+            .into_iter()
+            .skip(keep)
+            .filter_map(|installed| installed.path.parent().map(std::path::Path::to_path_buf))
+        {
+            if protected_dirs.contains(&dir) {
+                LOG.debug(&format!(
+                    "Keeping in-use version directory: {}",
+                    dir.display()
+                ));
+                continue;
+            }
+
+            match std::fs::remove_dir_all(&dir) {
+                Ok(()) => LOG.info(&format!("Removed old version directory: {}", dir.display())),
+                Err(e) => LOG.warn(&format!(
+                    "Failed to remove old version directory {}: {}",
+                    dir.display(),
+                    e
+                )),
+            }
+        }
+    }
+
+    /// Downloads the given version and checks that the installed binary actually runs,
+    /// deleting a broken install and downloading it once more before giving up
+    fn install_and_verify(
+        &self,
+        root: &std::path::Path,
+        version: &AdapterVersion,
+        settings: &AdapterSettings,
+    ) -> Result<String, BinaryManagerError> {
+        let binary_path = self.download_and_extract_binary(root, version, settings)?;
+        LOG.info(&format!(
+            "Successfully downloaded and extracted to: {}",
+            binary_path
+        ));
+        self.validate_binary(&binary_path)?;
+
+        if !settings.version_check.unwrap_or(true) {
+            return Ok(binary_path);
+        }
+
+        let Err(e) = self.check_binary_runs(&binary_path) else {
+            return Ok(binary_path);
+        };
+
+        LOG.warn(&format!(
+            "Installed netcoredbg {} failed to run ({}), re-downloading once",
+            version.tag_name, e
+        ));
+        let version_dir = Self::version_dir(root, &version.tag_name);
+        let _ = std::fs::remove_dir_all(&version_dir);
+
+        let binary_path = self.download_and_extract_binary(root, version, settings)?;
+        self.validate_binary(&binary_path)?;
+        if let Err(e) = self.check_binary_runs(&binary_path) {
+            let _ = std::fs::remove_dir_all(&version_dir);
+            return Err(BinaryManagerError::VerificationFailed {
+                version: version.tag_name.clone(),
+                message: format!("downloaded twice but still fails to run: {}", e),
+            });
+        }
+
+        Ok(binary_path)
+    }
+
+    /// Runs the resolved binary with `--version` before it is handed to Zed, so that a
+    /// netcoredbg that cannot start (e.g. missing libicu or an incompatible glibc) is reported
+    /// with its stderr instead of Zed only seeing the adapter exit. A binary that passed is not
+    /// checked again during this Zed session.
+    pub fn preflight(
+        &self,
+        binary_path: &str,
+        settings: &AdapterSettings,
+    ) -> Result<(), BinaryManagerError> {
+        if !settings.preflight_check.unwrap_or(true) {
+            return Ok(());
+        }
+        let mut passed = self
+            .preflight_passed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if passed.as_deref() == Some(binary_path) {
+            return Ok(());
+        }
+
+        self.check_binary_runs(binary_path)?;
+        *passed = Some(binary_path.to_string());
+        Ok(())
+    }
+
+    /// Runs `netcoredbg --version` and records the reported version. Executables with another
+    /// name are not covered by the extension's capabilities and are not checked.
+    fn check_binary_runs(&self, binary_path: &str) -> Result<(), BinaryManagerError> {
+        let path = std::path::Path::new(binary_path);
+        if path.file_stem().and_then(|stem| stem.to_str()) != Some("netcoredbg") {
+            LOG.info(&format!(
+                "Skipping the launch check of {}, which is not named netcoredbg",
+                binary_path
+            ));
+            return Ok(());
+        }
+        let output = platform::command_in(
+            path.parent().unwrap_or(std::path::Path::new("")),
+            "netcoredbg",
+            &[],
+        )
+        .arg("--version")
+        .output()
+        .map_err(|e| BinaryManagerError::LaunchFailed {
+            path: binary_path.to_string(),
+            message: format!("failed to execute it: {}", e),
+            stderr: Vec::new(),
+        })?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if output.status != Some(0) {
+            LOG.error(&format!(
+                "{} --version exited with {:?}. stdout: {} stderr: {}",
+                binary_path,
+                output.status,
+                stdout.trim(),
+                stderr.trim()
+            ));
+            let lines: Vec<String> = stderr
+                .lines()
+                .map(str::trim_end)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect();
+            return Err(BinaryManagerError::LaunchFailed {
+                path: binary_path.to_string(),
+                message: format!("'--version' exited with status {:?}", output.status),
+                stderr: lines[lines.len().saturating_sub(Self::STDERR_TAIL_LINES)..].to_vec(),
+            });
+        }
+        LOG.debug(&format!("{} --version: {}", binary_path, stdout.trim()));
+
+        let version = Self::parse_version_output(&stdout);
+        LOG.debug(&format!("netcoredbg reports version: {:?}", version));
+        if let Ok(mut installed_version) = self.installed_version.lock() {
+            *installed_version = version;
+        }
+
+        Ok(())
+    }
+
+    /// Extracts the version from `netcoredbg --version` output,
+    /// e.g. "NET Core debugger 3.1.2-1054 (...)" yields "3.1.2-1054"
+    fn parse_version_output(output: &str) -> Option<String> {
+        output
+            .split_whitespace()
+            .find(|token| {
+                token.starts_with(|c: char| c.is_ascii_digit())
+                    && token.contains('.')
+                    && token
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+            })
+            .map(str::to_string)
+    }
+
+    /// The version reported by the most recently verified netcoredbg install, if known
+    pub fn installed_version(&self) -> Option<String> {
+        self.installed_version
+            .lock()
+            .ok()
+            .and_then(|version| version.clone())
+    }
+
+    /// Resolves a user-provided path, already expanded into `expanded_path`, to the file or
+    /// directory it names. Absolute paths are returned unchanged; relative ones resolve against
+    /// the worktree root, then the extension's working directory, and fail naming both tried
+    /// paths when neither exists. `user_path` is the path as configured, for that error.
+    fn resolve_user_path(
+        expanded_path: &str,
+        user_path: &str,
+        scope: PathScope,
+        worktree: &Worktree,
+    ) -> Result<String, BinaryManagerError> {
+        if is_absolute_path(expanded_path) {
+            return Ok(expanded_path.to_string());
+        }
+
+        let mut candidates = vec![std::path::Path::new(&worktree.root_path()).join(expanded_path)];
+        if let Ok(cwd) = std::env::current_dir() {
+            candidates.push(cwd.join(expanded_path));
+        }
+        if let Some(found) = candidates.iter().find(|candidate| candidate.exists()) {
+            return Self::to_os_full_path_str(found);
+        }
+
+        Err(BinaryManagerError::UserPathInvalid {
+            scope,
+            path: user_path.to_string(),
+            message: format!(
+                "the relative path does not exist. Tried: [{}]",
+                candidates
+                    .iter()
+                    .map(|candidate| candidate.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        })
+    }
+
+    /// Locates the executable inside a user-provided directory, looking directly inside it
+    /// and one level down since upstream archives nest a `netcoredbg/` folder
+    fn find_binary_in_user_dir(
+        dir: &std::path::Path,
+        scope: PathScope,
+    ) -> Result<String, BinaryManagerError> {
+        let exe_name = Self::get_executable_name();
+
+        let mut candidates = vec![dir.join(exe_name)];
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            let mut nested: Vec<std::path::PathBuf> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .map(|path| path.join(exe_name))
+                .collect();
+            nested.sort();
+            candidates.extend(nested);
+        }
+
+        if let Some(found) = candidates.iter().find(|candidate| candidate.is_file()) {
+            LOG.debug(&format!(
+                "Resolved user-provided directory {} to {}",
+                dir.display(),
+                found.display()
+            ));
+            return Self::to_os_full_path_str(found);
+        }
+
+        Err(BinaryManagerError::UserPathInvalid {
+            scope,
+            path: dir.display().to_string(),
+            message: format!(
+                "the directory contains no {}. Searched: [{}]",
+                exe_name,
+                candidates
+                    .iter()
+                    .map(|candidate| candidate.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        })
+    }
+
+    /// Validates that the binary exists and is not truncated. The extension sandbox exposes no
+    /// permission bits; installs are made executable with `zed::make_file_executable`.
+    fn validate_binary(&self, binary_path: &str) -> Result<(), BinaryManagerError> {
+        let path = std::path::Path::new(binary_path);
+
+        if !path.exists() {
+            return Err(BinaryManagerError::FileSystem(format!(
+                "netcoredbg binary not found at: {}",
+                binary_path
+            )));
+        }
+
+        if !path.is_file() {
+            return Err(BinaryManagerError::FileSystem(format!(
+                "netcoredbg path is not a file: {}",
+                binary_path
+            )));
+        }
+
+        let metadata = std::fs::metadata(path).map_err(|e| {
+            BinaryManagerError::FileSystem(format!("Failed to inspect {}: {}", binary_path, e))
+        })?;
+        if metadata.len() < Self::MIN_BINARY_SIZE {
+            return Err(BinaryManagerError::FileSystem(format!(
+                "netcoredbg binary at {} is only {} bytes and likely truncated",
+                binary_path,
+                metadata.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Whether an on-disk install can be reused. A broken or tampered install is deleted, with
+    /// the reason logged, so that the caller falls through to downloading it again.
+    fn is_intact_install(&self, binary_path: &std::path::Path, settings: &AdapterSettings) -> bool {
+        if !binary_path.exists() {
+            return false;
+        }
+        let Err(e) = self
+            .validate_binary(&binary_path.to_string_lossy())
+            .map_err(|e| e.to_string())
+            .and_then(|()| Self::verify_integrity(binary_path, settings))
+        else {
+            return true;
+        };
+
+        let Some(version_dir) = binary_path.parent().filter(|dir| {
+            dir.file_name()
+                .is_some_and(|name| Self::is_version_dir_name(&name.to_string_lossy()))
+        }) else {
+            LOG.warn(&format!("Ignoring broken binary: {}", e));
+            return false;
+        };
+        LOG.warn(&format!(
+            "Discarding broken install {}: {}",
+            version_dir.display(),
+            e
+        ));
+        if let Err(e) = std::fs::remove_dir_all(version_dir) {
+            LOG.warn(&format!(
+                "Failed to remove broken install {}: {}",
+                version_dir.display(),
+                e
+            ));
+        }
+        false
+    }
+
+    fn absolute_path(path: &std::path::Path) -> Result<std::path::PathBuf, BinaryManagerError> {
+        if path.is_absolute() {
+            Ok(path.to_path_buf())
+        } else {
+            std::env::current_dir()
+                .map_err(|e| {
+                    BinaryManagerError::FileSystem(format!("Failed to get current dir: {}", e))
+                })
+                .map(|cwd| cwd.join(path))
+        }
+    }
+
+    fn to_os_full_path_str(path: &std::path::Path) -> Result<String, BinaryManagerError> {
+        let los = path.to_string_lossy();
+
+        let s = match platform::os() {
+            // WASI paths look like "/C:/path"; netcoredbg is spawned with a native Windows path
+            zed::Os::Windows => to_long_windows_path(&normalize_windows_path(&los)),
+            _ => los.to_string(),
+        };
+
+        Ok(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    const ABC_DIGEST: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    /// Creates `netcoredbg_v{tag}` below `root` with an executable large enough to pass
+    /// validation, returning the executable's path
+    fn fake_install(root: &TempDir, tag: &str) -> std::path::PathBuf {
+        root.write(
+            &format!("netcoredbg_v{}/netcoredbg", tag),
+            vec![0u8; BinaryManager::MIN_BINARY_SIZE as usize],
+        )
+    }
+
+    fn tags(installed: &[InstalledVersion]) -> Vec<&str> {
+        installed
+            .iter()
+            .map(|version| version.tag.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn verify_checksum_accepts_matching_digest() {
+        let dir = TempDir::new();
+        let path = dir.write("netcoredbg.tar.gz", "abc");
+        assert!(BinaryManager::verify_checksum(&path, ABC_DIGEST).is_ok());
+        // Digests are compared case-insensitively, ignoring surrounding whitespace
+        let upper = format!(" {}\n", ABC_DIGEST.to_ascii_uppercase());
+        assert!(BinaryManager::verify_checksum(&path, &upper).is_ok());
+    }
+
+    #[test]
+    fn verify_checksum_rejects_mismatched_digest() {
+        let dir = TempDir::new();
+        let path = dir.write("netcoredbg.tar.gz", "abd");
+        match BinaryManager::verify_checksum(&path, ABC_DIGEST) {
+            Err(BinaryManagerError::ChecksumMismatch {
+                expected, actual, ..
+            }) => {
+                assert_eq!(expected, ABC_DIGEST);
+                assert_ne!(actual, ABC_DIGEST);
+            }
+            other => panic!("expected a checksum mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_checksum_rejects_malformed_digest() {
+        let dir = TempDir::new();
+        let path = dir.write("netcoredbg.tar.gz", "abc");
+        assert!(matches!(
+            BinaryManager::verify_checksum(&path, "not-a-sha256-digest"),
+            Err(BinaryManagerError::ChecksumMismatch { .. })
+        ));
+        assert!(matches!(
+            BinaryManager::verify_checksum(&dir.path().join("missing"), ABC_DIGEST),
+            Err(BinaryManagerError::FileSystem(_))
+        ));
+    }
+
+    #[test]
+    fn offline_fallback_picks_newest_intact_install() {
+        let root = TempDir::new();
+        fake_install(&root, "3.1.2-1054");
+        fake_install(&root, "3.1.10-1100");
+        fake_install(&root, "3.0.0-1018");
+        let manager = BinaryManager::new();
+
+        let newest = manager
+            .find_newest_installed(root.path(), &AdapterSettings::default())
+            .expect("an installed version");
+        assert_eq!(newest.tag, "3.1.10-1100");
+        assert!(newest.path.ends_with("netcoredbg_v3.1.10-1100/netcoredbg"));
+    }
+
+    #[test]
+    fn offline_fallback_skips_unusable_installs() {
+        let root = TempDir::new();
+        fake_install(&root, "3.1.2-1054");
+        // Newer, but interrupted, truncated, empty or a pre-release
+        fake_install(&root, "3.2.0-1200.partial");
+        root.write("netcoredbg_v3.3.0-1300/netcoredbg", "truncated");
+        std::fs::create_dir_all(root.path().join("netcoredbg_v3.4.0-1400")).unwrap();
+        fake_install(&root, "3.5.0-rc1");
+        let manager = BinaryManager::new();
+
+        let newest = manager.find_newest_installed(root.path(), &AdapterSettings::default());
+        assert_eq!(
+            newest.map(|installed| installed.tag).as_deref(),
+            Some("3.1.2-1054")
+        );
+
+        let allow_prerelease = AdapterSettings {
+            allow_prerelease: Some(true),
+            ..AdapterSettings::default()
+        };
+        let newest = manager.find_newest_installed(root.path(), &allow_prerelease);
+        assert_eq!(
+            newest.map(|installed| installed.tag).as_deref(),
+            Some("3.5.0-rc1")
+        );
+    }
+
+    #[test]
+    fn offline_fallback_finds_nothing_in_empty_or_missing_root() {
+        let root = TempDir::new();
+        let manager = BinaryManager::new();
+        assert!(manager
+            .find_newest_installed(root.path(), &AdapterSettings::default())
+            .is_none());
+        assert!(BinaryManager::installed_versions(&root.path().join("missing")).is_empty());
+    }
+
+    fn forced_asset_settings() -> AdapterSettings {
+        AdapterSettings {
+            force_asset: Some("netcoredbg-linux-x64.tar.gz".to_string()),
+            ..AdapterSettings::default()
+        }
+    }
+
+    fn release_cache(fetched_at: u64) -> ReleaseCache {
+        ReleaseCache {
+            repo: "marcptrs/netcoredbg".to_string(),
+            asset_name: "netcoredbg-linux-x64.tar.gz".to_string(),
+            pre_release: false,
+            fetched_at,
+            version: AdapterVersion {
+                tag_name: "3.1.2-1054".to_string(),
+                download_url: "https://example.com/netcoredbg-linux-x64.tar.gz".to_string(),
+                checksum_url: None,
+            },
+        }
+    }
+
+    fn rate_limit_error() -> BinaryManagerError {
+        BinaryManagerError::ReleaseFetch {
+            repo: "marcptrs/netcoredbg".to_string(),
+            tag: None,
+            message: "API rate limit exceeded (403)".to_string(),
+            available_tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn release_cache_round_trips_for_matching_lookup() {
+        let root = TempDir::new();
+        let settings = forced_asset_settings();
+        let version = release_cache(0).version;
+        BinaryManager::write_release_cache(root.path(), "marcptrs/netcoredbg", &settings, &version);
+
+        let cache =
+            BinaryManager::read_release_cache(root.path(), "marcptrs/netcoredbg", &settings)
+                .expect("a cached release");
+        assert_eq!(cache.version.tag_name, version.tag_name);
+        assert!(BinaryManager::fresh_cached_release(&cache, &settings).is_some());
+
+        // A different repository, asset or pre-release preference needs its own lookup
+        assert!(
+            BinaryManager::read_release_cache(root.path(), "fork/netcoredbg", &settings).is_none()
+        );
+        let prerelease = AdapterSettings {
+            allow_prerelease: Some(true),
+            ..forced_asset_settings()
+        };
+        assert!(
+            BinaryManager::read_release_cache(root.path(), "marcptrs/netcoredbg", &prerelease)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn missing_or_corrupt_release_cache_is_ignored() {
+        let root = TempDir::new();
+        let settings = forced_asset_settings();
+        assert!(
+            BinaryManager::read_release_cache(root.path(), "marcptrs/netcoredbg", &settings)
+                .is_none()
+        );
+
+        root.write(BinaryManager::RELEASE_CACHE_FILE, "{ not json");
+        assert!(
+            BinaryManager::read_release_cache(root.path(), "marcptrs/netcoredbg", &settings)
+                .is_none()
+        );
+        assert!(BinaryManager::stale_release_fallback(None, rate_limit_error()).is_err());
+    }
+
+    #[test]
+    fn stale_release_cache_is_only_used_when_rate_limited() {
+        let settings = forced_asset_settings();
+        let stale = release_cache(BinaryManager::unix_timestamp() - 25 * 60 * 60);
+        assert!(BinaryManager::fresh_cached_release(&stale, &settings).is_none());
+
+        let longer_ttl = AdapterSettings {
+            release_cache_ttl_hours: Some(48),
+            ..forced_asset_settings()
+        };
+        assert!(BinaryManager::fresh_cached_release(&stale, &longer_ttl).is_some());
+
+        let version = BinaryManager::stale_release_fallback(Some(stale), rate_limit_error())
+            .expect("stale release");
+        assert_eq!(version.tag_name, "3.1.2-1054");
+
+        let offline = BinaryManagerError::ReleaseFetch {
+            repo: "marcptrs/netcoredbg".to_string(),
+            tag: None,
+            message: "connection refused".to_string(),
+            available_tags: Vec::new(),
+        };
+        let stale = release_cache(0);
+        assert!(BinaryManager::stale_release_fallback(Some(stale), offline).is_err());
+    }
+
+    #[test]
+    fn detects_musl_from_ldd_output() {
+        assert!(BinaryManager::is_musl_ldd_output(
+            "musl libc (x86_64)\nVersion 1.2.4\nDynamic Program Loader\n"
+        ));
+        assert!(!BinaryManager::is_musl_ldd_output(
+            "ldd (GNU libc) 2.36\nCopyright (C) 2022 Free Software Foundation, Inc.\n"
+        ));
+        assert!(!BinaryManager::is_musl_ldd_output(
+            "ldd (Ubuntu GLIBC 2.35-0ubuntu3.6) 2.35"
+        ));
+        assert!(!BinaryManager::is_musl_ldd_output(""));
+    }
+
+    #[test]
+    fn incomplete_install_is_discarded() {
+        let root = TempDir::new();
+        let binary = root.write("netcoredbg_v3.1.2-1054/netcoredbg", "truncated");
+        let manager = BinaryManager::new();
+
+        assert!(!manager.is_intact_install(&binary, &AdapterSettings::default()));
+        assert!(!root.path().join("netcoredbg_v3.1.2-1054").exists());
+    }
+
+    #[test]
+    fn intact_install_is_kept() {
+        let root = TempDir::new();
+        let binary = fake_install(&root, "3.1.2-1054");
+        let manager = BinaryManager::new();
+
+        assert!(manager.is_intact_install(&binary, &AdapterSettings::default()));
+        assert!(binary.exists());
+    }
+
+    #[test]
+    fn interrupted_installs_are_removed_unless_still_running() {
+        let root = TempDir::new();
+        fake_install(&root, "3.1.2-1054");
+        fake_install(&root, "3.1.3-1062.partial");
+        fake_install(&root, "3.1.4-1070.partial");
+        let _running = InstallLock::acquire(&BinaryManager::lock_path(root.path(), "3.1.4-1070"))
+            .expect("lock");
+
+        BinaryManager::remove_partial_installs(root.path());
+        assert!(root.path().join("netcoredbg_v3.1.2-1054").exists());
+        assert!(!root.path().join("netcoredbg_v3.1.3-1062.partial").exists());
+        assert!(root.path().join("netcoredbg_v3.1.4-1070.partial").exists());
+    }
+
+    #[test]
+    fn waits_for_lock_holder_and_finds_its_install() {
+        let root = TempDir::new();
+        let lock_path = BinaryManager::lock_path(root.path(), "3.1.2-1054");
+        let held = InstallLock::acquire(&lock_path).expect("lock");
+
+        let installer = std::thread::scope(|scope| {
+            let installer = scope.spawn(|| {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                let binary = fake_install(&root, "3.1.2-1054");
+                drop(held);
+                binary
+            });
+            let _lock = InstallLock::acquire(&lock_path).expect("lock after waiting");
+            assert!(BinaryManager::new().is_intact_install(
+                &root.path().join("netcoredbg_v3.1.2-1054/netcoredbg"),
+                &AdapterSettings::default()
+            ));
+            installer.join().unwrap()
+        });
+        assert!(installer.exists());
+        assert!(!lock_path.exists());
+    }
+
+    #[test]
+    fn breaks_stale_locks() {
+        let root = TempDir::new();
+        let lock_path = root.write("netcoredbg_v3.1.2-1054.lock", "");
+        let stale = std::time::SystemTime::now() - 2 * InstallLock::STALE_AFTER;
+        std::fs::File::options()
+            .write(true)
+            .open(&lock_path)
+            .and_then(|file| file.set_modified(stale))
+            .unwrap();
+        assert!(!InstallLock::is_held(&lock_path));
+
+        let started = std::time::Instant::now();
+        let lock = InstallLock::acquire(&lock_path).expect("stale lock is broken");
+        assert!(started.elapsed() < InstallLock::POLL_INTERVAL);
+        assert!(InstallLock::is_held(&lock_path));
+        drop(lock);
+        assert!(!lock_path.exists());
+    }
+
+    #[test]
+    fn lists_installed_versions_newest_first() {
+        let root = TempDir::new();
+        let older = fake_install(&root, "3.0.0-1018");
+        fake_install(&root, "3.1.2-1054");
+        // A release archive nested one directory deep is flattened while listing
+        root.write(
+            "netcoredbg_v3.1.10-1100/netcoredbg/netcoredbg",
+            vec![0u8; BinaryManager::MIN_BINARY_SIZE as usize + 1],
+        );
+        fake_install(&root, "3.2.0-1200.partial");
+        root.write("netcoredbg_v3.3.0-1300/README.md", "no executable");
+        root.write("netcoredbg_release_cache.json", "{}");
+
+        let installed = BinaryManager::installed_versions(root.path());
+        assert_eq!(
+            tags(&installed),
+            ["3.1.10-1100", "3.1.2-1054", "3.0.0-1018"]
+        );
+        assert_eq!(installed[0].size, BinaryManager::MIN_BINARY_SIZE + 1);
+        assert!(installed[0]
+            .path
+            .ends_with("netcoredbg_v3.1.10-1100/netcoredbg"));
+        assert_eq!(installed[2].path, older);
+    }
+
+    /// A synthetic extracted release: the runtime files plus docs and test fixtures
+    fn extracted_release(temp: &TempDir) {
+        for file in [
+            "netcoredbg/netcoredbg",
+            "netcoredbg/ManagedPart.dll",
+            "netcoredbg/Microsoft.CodeAnalysis.dll",
+            "netcoredbg/libdbgshim.so",
+            "netcoredbg/libcoreclrtraceptprovider.so.1",
+            "netcoredbg/LICENSE",
+            "netcoredbg/THIRD-PARTY-NOTICES.TXT",
+            "netcoredbg/docs/usage.md",
+            "netcoredbg/tests/fixture.json",
+        ] {
+            temp.write(file, file);
+        }
+    }
+
+    fn copied_files(dir: &std::path::Path) -> Vec<String> {
+        let mut files = Vec::new();
+        fn walk(base: &std::path::Path, dir: &std::path::Path, files: &mut Vec<String>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    walk(base, &path, files);
+                } else {
+                    let relative = path.strip_prefix(base).unwrap();
+                    files.push(relative.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+        walk(dir, dir, &mut files);
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn copies_only_runtime_files_of_known_layouts() {
+        let temp = TempDir::new();
+        extracted_release(&temp);
+        let version_dir = temp.path().join("netcoredbg_v3.1.2-1054");
+        let manager = BinaryManager::new();
+
+        manager
+            .copy_extracted_content(
+                &temp.path().join("netcoredbg"),
+                &version_dir,
+                &AdapterSettings::default(),
+            )
+            .expect("copy");
+        assert_eq!(
+            copied_files(&version_dir),
+            [
+                "LICENSE",
+                "ManagedPart.dll",
+                "Microsoft.CodeAnalysis.dll",
+                "THIRD-PARTY-NOTICES.TXT",
+                "libcoreclrtraceptprovider.so.1",
+                "libdbgshim.so",
+                "netcoredbg",
+            ]
+        );
+    }
+
+    #[test]
+    fn copies_everything_when_asked_or_layout_is_unknown() {
+        let temp = TempDir::new();
+        extracted_release(&temp);
+        let version_dir = temp.path().join("netcoredbg_v3.1.2-1054");
+        let copy_all = AdapterSettings {
+            copy_all_files: Some(true),
+            ..AdapterSettings::default()
+        };
+        BinaryManager::new()
+            .copy_extracted_content(&temp.path().join("netcoredbg"), &version_dir, &copy_all)
+            .expect("copy");
+        assert_eq!(copied_files(&version_dir).len(), 9);
+
+        let unknown = TempDir::new();
+        unknown.write("release/netcoredbg", "netcoredbg");
+        unknown.write("release/README.md", "readme");
+        unknown.write("release/data/symbols.bin", "data");
+        let version_dir = unknown.path().join("netcoredbg_v3.1.2-1054");
+        BinaryManager::new()
+            .copy_extracted_content(
+                &unknown.path().join("release"),
+                &version_dir,
+                &AdapterSettings::default(),
+            )
+            .expect("copy");
+        assert_eq!(
+            copied_files(&version_dir),
+            ["README.md", "data/symbols.bin", "netcoredbg"]
+        );
+    }
+
+    #[test]
+    fn infers_asset_format_from_file_name() {
+        let format = |url| BinaryManager::asset_format(url).map_err(|e| e.to_string());
+        assert!(matches!(
+            format("https://example.com/netcoredbg-win-x64.zip"),
+            Ok(AssetFormat::Archive(DownloadedFileType::Zip))
+        ));
+        assert!(matches!(
+            format("https://example.com/netcoredbg-linux-x64.tar.gz?raw=true"),
+            Ok(AssetFormat::Archive(DownloadedFileType::GzipTar))
+        ));
+        assert!(matches!(
+            format("https://example.com/netcoredbg-linux-x64.txz"),
+            Ok(AssetFormat::TarXz)
+        ));
+        assert!(matches!(
+            format("https://example.com/netcoredbg"),
+            Ok(AssetFormat::RawExecutable)
+        ));
+        assert!(matches!(
+            format("https://example.com/netcoredbg.exe"),
+            Ok(AssetFormat::RawExecutable)
+        ));
+        assert!(format("https://example.com/netcoredbg.7z")
+            .unwrap_err()
+            .starts_with("Unsupported file type for asset"));
+    }
+
+    #[test]
+    fn unsupported_download_url_names_the_setting() {
+        let error = BinaryManager::direct_download_version(
+            "https://example.com/netcoredbg.7z",
+            &AdapterSettings::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            BinaryManagerError::InvalidSetting {
+                setting: "download_url",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn recent_install_skips_release_lookup_without_cache() {
+        let root = TempDir::new();
+        fake_install(&root, "3.1.2-1054");
+        let settings = forced_asset_settings();
+        let installed = &BinaryManager::installed_versions(root.path())[0];
+        assert!(BinaryManager::can_skip_release_lookup(
+            root.path(),
+            &settings,
+            installed
+        ));
+
+        let expired = InstalledVersion {
+            installed_at: std::time::SystemTime::now()
+                - std::time::Duration::from_secs(25 * 60 * 60),
+            ..installed.clone()
+        };
+        assert!(!BinaryManager::can_skip_release_lookup(
+            root.path(),
+            &settings,
+            &expired
+        ));
+
+        // A recent update check recorded in the manifest counts as well
+        let binary_path = installed.path.to_string_lossy().into_owned();
+        BinaryManager::write_manifest(root.path(), "3.1.2-1054", &binary_path, &settings);
+        let mut manifest = BinaryManager::load_manifest(root.path()).unwrap();
+        manifest.update_checked_at = BinaryManager::unix_timestamp();
+        BinaryManager::store_manifest(root.path(), &manifest).unwrap();
+        assert!(BinaryManager::can_skip_release_lookup(
+            root.path(),
+            &settings,
+            &expired
+        ));
+    }
+
+    #[test]
+    fn fresh_release_cache_is_left_to_the_lookup() {
+        let root = TempDir::new();
+        fake_install(&root, "3.1.2-1054");
+        let settings = forced_asset_settings();
+        let version = release_cache(0).version;
+        BinaryManager::write_release_cache(root.path(), "marcptrs/netcoredbg", &settings, &version);
+
+        let installed = &BinaryManager::installed_versions(root.path())[0];
+        assert!(!BinaryManager::can_skip_release_lookup(
+            root.path(),
+            &settings,
+            installed
+        ));
+    }
+
+    #[test]
+    fn validate_binary_rejects_missing_directories_and_truncated_files() {
+        let root = TempDir::new();
+        let manager = BinaryManager::new();
+        let valid = fake_install(&root, "3.1.2-1054");
+        let truncated = root.write("netcoredbg_v3.0.0-1018/netcoredbg", "");
+        let path = |path: &std::path::Path| path.to_string_lossy().into_owned();
+
+        assert!(manager.validate_binary(&path(&valid)).is_ok());
+        assert!(manager
+            .validate_binary(&path(&truncated))
+            .unwrap_err()
+            .to_string()
+            .contains("is only 0 bytes"));
+        assert!(manager
+            .validate_binary(&path(&root.path().join("missing")))
+            .is_err());
+        assert!(manager
+            .validate_binary(&path(&root.path().join("netcoredbg_v3.1.2-1054")))
+            .unwrap_err()
+            .to_string()
+            .contains("is not a file"));
+    }
+
+    #[test]
+    fn redacts_token_from_error_strings() {
+        let token = "not-a-github-shaped-token";
+        let message = format!("GitHub API error: Bad credentials for Bearer {}", token);
+        let redacted = BinaryManager::redact(&message, token);
+        assert!(!redacted.contains(token));
+        assert_eq!(
+            redacted,
+            "GitHub API error: Bad credentials for Bearer [REDACTED]"
+        );
+        assert_eq!(BinaryManager::redact("no token here", ""), "no token here");
+    }
+
+    /// Installs `previous` and the update `tag`, recording the update as the binary in use whose
+    /// first launch is pending
+    fn updated_install(root: &TempDir, previous: &str, tag: &str) -> String {
+        fake_install(root, previous);
+        let binary = fake_install(root, tag).to_string_lossy().into_owned();
+        BinaryManager::write_manifest(root.path(), tag, &binary, &AdapterSettings::default());
+        BinaryManager::expect_first_launch(root.path(), tag);
+        binary
+    }
+
+    #[test]
+    fn first_launch_that_wrote_its_engine_log_keeps_the_update() {
+        let root = TempDir::new();
+        let binary = updated_install(&root, "3.1.2-1054", "3.1.3-1062");
+        let log = BinaryManager::first_launch_log(&binary).expect("first launch pending");
+        assert_eq!(log, root.path().join(BinaryManager::FIRST_LAUNCH_LOG_FILE));
+
+        BinaryManager::record_first_launch(&binary, &log.to_string_lossy());
+        std::fs::write(&log, "<- (E) {\"command\":\"initialize\"}").unwrap();
+        assert!(!BinaryManager::check_first_launch(root.path()));
+
+        let manifest = BinaryManager::load_manifest(root.path()).unwrap();
+        assert_eq!(manifest.first_launch, None);
+        assert!(manifest.bad_versions.is_empty());
+        assert!(BinaryManager::first_launch_log(&binary).is_none());
+    }
+
+    #[test]
+    fn first_launch_that_exited_immediately_rolls_back() {
+        let root = TempDir::new();
+        let binary = updated_install(&root, "3.1.2-1054", "3.1.3-1062");
+        let log = root.path().join(BinaryManager::FIRST_LAUNCH_LOG_FILE);
+        BinaryManager::record_first_launch(&binary, &log.to_string_lossy());
+
+        assert!(BinaryManager::check_first_launch(root.path()));
+        let manifest = BinaryManager::load_manifest(root.path()).unwrap();
+        assert_eq!(manifest.bad_versions, ["3.1.3-1062"]);
+        assert_eq!(manifest.first_launch, None);
+
+        let manager = BinaryManager::new();
+        assert!(manager
+            .read_manifest(root.path(), &AdapterSettings::default())
+            .is_none());
+        let fallback = manager
+            .find_newest_installed(root.path(), &AdapterSettings::default())
+            .expect("previous install");
+        assert_eq!(fallback.tag, "3.1.2-1054");
+    }
+
+    #[test]
+    fn first_launch_is_not_judged_before_it_happens() {
+        let root = TempDir::new();
+        let binary = updated_install(&root, "3.1.2-1054", "3.1.3-1062");
+        assert!(!BinaryManager::check_first_launch(root.path()));
+        assert!(BinaryManager::first_launch_log(&binary).is_some());
+
+        // Launches of other versions are not the update's first launch
+        let previous = root.path().join("netcoredbg_v3.1.2-1054/netcoredbg");
+        assert!(BinaryManager::first_launch_log(&previous.to_string_lossy()).is_none());
+    }
+
+    #[test]
+    fn finds_binary_in_user_directory_or_one_level_down() {
+        let dir = TempDir::new();
+        let direct = dir.write("tools/netcoredbg", "binary");
+        let nested = dir.write("extracted/netcoredbg/netcoredbg", "binary");
+        let find = |path: &std::path::Path| {
+            BinaryManager::find_binary_in_user_dir(path, PathScope::ZedSettings)
+        };
+
+        assert_eq!(
+            find(&dir.path().join("tools")).unwrap(),
+            direct.to_string_lossy()
+        );
+        assert_eq!(
+            find(&dir.path().join("extracted")).unwrap(),
+            nested.to_string_lossy()
+        );
+        dir.write("empty/docs/README.md", "readme");
+        assert!(matches!(
+            find(&dir.path().join("empty")),
+            Err(BinaryManagerError::UserPathInvalid {
+                scope: PathScope::ZedSettings,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn starts_downloads_in_an_empty_temp_directory_and_removes_it() {
+        let dir = TempDir::new();
+        let path = dir.path().join("temp_netcoredbg_v1.0.0");
+        dir.write("temp_netcoredbg_v1.0.0/netcoredbg/stale.dll", "partial");
+
+        let temp_dir = ScratchDir::create(path.clone()).unwrap();
+        assert_eq!(std::fs::read_dir(&path).unwrap().count(), 0);
+        dir.write("temp_netcoredbg_v1.0.0/archive.tar.xz", "partial");
+
+        // Dropped on the error path of a failed download as well as after a successful install
+        drop(temp_dir);
+        assert!(!path.exists());
     }
 }
@@ -0,0 +1,423 @@
+use crate::logger::{Logger, ScopedLogger};
+use zed_extension_api::serde_json::{self, Map, Value};
+
+const LOG: ScopedLogger = Logger::scoped("compat");
+
+/// `type` of the C# debug configurations of VS Code
+const CORECLR_TYPE: &str = "coreclr";
+
+/// Location of VS Code's debug configurations relative to the worktree root
+const LAUNCH_JSON_FILE: &str = ".vscode/launch.json";
+
+/// VS Code keys with a different name here
+const RENAMED_KEYS: [(&str, &str); 1] = [("launchSettingsProfile", "launchProfile")];
+
+/// VS Code keys without an equivalent here, with the reason they are dropped
+const IGNORED_KEYS: [(&str, &str); 8] = [
+    (
+        "internalConsoleOptions",
+        "Zed decides when to show the debug console",
+    ),
+    ("postDebugTask", "Zed runs no tasks after a debug session"),
+    ("launchBrowser", "the extension cannot open a browser"),
+    (
+        "logging",
+        "set the log level with the 'netcoredbg.log_level' adapter setting instead",
+    ),
+    ("requireExactSource", "netcoredbg does not support it"),
+    (
+        "sourceLinkOptions",
+        "netcoredbg does not support Source Link; set 'enableSourceLink' to step into library code",
+    ),
+    (
+        "checkForDevCert",
+        "the extension does not manage certificates",
+    ),
+    (
+        "launchSettingsFilePath",
+        "launch profiles are read from the project's Properties/launchSettings.json",
+    ),
+];
+
+/// Translates the keys of a VS Code `coreclr` configuration that differ from the ones of this
+/// extension, so that configurations copied from `launch.json` work. Keys that cannot be
+/// supported are removed with a warning instead of failing the session.
+pub fn translate_coreclr(config: &mut Map<String, Value>) {
+    let is_coreclr = match config.remove("type") {
+        Some(Value::String(kind)) if kind == CORECLR_TYPE => {
+            LOG.info("Translating a VS Code 'coreclr' configuration");
+            true
+        }
+        Some(kind) => {
+            LOG.warn(&format!(
+                "'type' {} is ignored; netcoredbg debugs 'coreclr' configurations",
+                kind
+            ));
+            false
+        }
+        None => false,
+    };
+    // Zed names configurations with `label`
+    config.remove("name");
+
+    for (vscode_key, key) in RENAMED_KEYS {
+        if let Some(value) = config.remove(vscode_key) {
+            if config.contains_key(key) {
+                LOG.warn(&format!(
+                    "'{}' is ignored, as '{}' is also set",
+                    vscode_key, key
+                ));
+            } else {
+                config.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    if let Some(task) = config.remove("preLaunchTask") {
+        let is_launch = config.get("request").and_then(Value::as_str) == Some("launch");
+        if is_launch && !config.contains_key("buildProject") {
+            LOG.info(&format!(
+                "'preLaunchTask' {} is replaced by building the project before launching ('buildProject': true)",
+                task
+            ));
+            config.insert("buildProject".to_string(), Value::Bool(true));
+        } else {
+            LOG.warn(&format!(
+                "'preLaunchTask' {} is ignored; use the 'build' key of the Zed debug scenario to run a task first",
+                task
+            ));
+        }
+    }
+
+    for (key, reason) in IGNORED_KEYS {
+        if config.remove(key).is_some() {
+            LOG.warn(&format!("'{}' is ignored: {}", key, reason));
+        }
+    }
+
+    // VS Code starts programs in a terminal on its own, which netcoredbg cannot
+    let terminal_console = config
+        .get("console")
+        .and_then(Value::as_str)
+        .filter(|console| is_coreclr && crate::TERMINAL_CONSOLES.contains(console))
+        .map(str::to_string);
+    if let Some(console) = terminal_console {
+        config.remove("console");
+        LOG.warn(&format!(
+            "'console' '{}' is ignored: netcoredbg cannot start the program in a terminal, so its output goes to the debug console",
+            console
+        ));
+    }
+}
+
+/// Reads the configuration named `name` from the worktree's `.vscode/launch.json`. Compound
+/// configurations and configurations this extension cannot debug are reported as errors
+/// naming the offending keys.
+pub fn import_launch_json(worktree_root: &str, name: &str) -> Result<Map<String, Value>, String> {
+    let path = std::path::Path::new(worktree_root).join(LAUNCH_JSON_FILE);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let launch_json: Value = serde_json::from_str(&strip_jsonc(&content))
+        .map_err(|e| format!("Invalid '{}': {}", path.display(), e))?;
+
+    let named = |key: &str| {
+        launch_json
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_object)
+            .find(|config| config.get("name").and_then(Value::as_str) == Some(name))
+    };
+    if named("compounds").is_some() {
+        return Err(format!(
+            "'{}' is a compound configuration in '{}', which cannot be imported; import its configurations one by one",
+            name,
+            path.display()
+        ));
+    }
+    let config = named("configurations").cloned().ok_or_else(|| {
+        let names: Vec<String> = launch_json
+            .get("configurations")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|config| config.get("type").and_then(Value::as_str) == Some(CORECLR_TYPE))
+            .filter_map(|config| config.get("name").and_then(Value::as_str))
+            .map(|name| format!("'{}'", name))
+            .collect();
+        format!(
+            "No configuration named '{}' in '{}'. coreclr configurations: {}",
+            name,
+            path.display(),
+            if names.is_empty() {
+                "none".to_string()
+            } else {
+                names.join(", ")
+            }
+        )
+    })?;
+
+    let mut offending: Vec<String> = Vec::new();
+    if config.get("type").and_then(Value::as_str) != Some(CORECLR_TYPE) {
+        offending.push("type".to_string());
+    }
+    for (key, value) in &config {
+        if value.to_string().contains("${input:") {
+            offending.push(key.clone());
+        }
+    }
+    if !offending.is_empty() {
+        return Err(format!(
+            "Configuration '{}' in '{}' uses features netcoredbg in Zed cannot support: '{}'. Only 'coreclr' configurations without input variables can be imported.",
+            name,
+            path.display(),
+            offending.join("', '")
+        ));
+    }
+    LOG.info(&format!("Imported '{}' from {}", name, path.display()));
+    Ok(config)
+}
+
+/// Removes the `//` and `/* */` comments and trailing commas VS Code allows in its JSON files
+fn strip_jsonc(content: &str) -> String {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut without_comments = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            _ if in_string => {
+                without_comments.push(c);
+                match c {
+                    '\\' => without_comments.extend(chars.next()),
+                    '"' => in_string = false,
+                    _ => {}
+                }
+            }
+            ('/', Some('/')) => while chars.next_if(|&c| c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => {
+                in_string = c == '"';
+                without_comments.push(c);
+            }
+        }
+    }
+
+    // A comma followed only by whitespace and a closing bracket is dropped
+    let chars: Vec<char> = without_comments.chars().collect();
+    let mut output = String::with_capacity(without_comments.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, &c) in chars.iter().enumerate() {
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ','
+            && chars[index + 1..]
+                .iter()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|c| matches!(c, '}' | ']'))
+        {
+            continue;
+        }
+        output.push(c);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn translated(config: Value) -> Value {
+        let Value::Object(mut config) = config else {
+            panic!("configurations are objects");
+        };
+        translate_coreclr(&mut config);
+        Value::Object(config)
+    }
+
+    #[test]
+    fn translates_vscode_launch_configurations() {
+        let cases = [
+            // The configuration the C# extension generates for a console app
+            (
+                serde_json::json!({
+                    "name": ".NET Core Launch (console)",
+                    "type": "coreclr",
+                    "request": "launch",
+                    "preLaunchTask": "build",
+                    "program": "${workspaceFolder}/bin/Debug/net8.0/App.dll",
+                    "args": [],
+                    "cwd": "${workspaceFolder}",
+                    "console": "internalConsole",
+                    "stopAtEntry": false
+                }),
+                serde_json::json!({
+                    "request": "launch",
+                    "buildProject": true,
+                    "program": "${workspaceFolder}/bin/Debug/net8.0/App.dll",
+                    "args": [],
+                    "cwd": "${workspaceFolder}",
+                    "console": "internalConsole",
+                    "stopAtEntry": false
+                }),
+            ),
+            // ...and for a web app
+            (
+                serde_json::json!({
+                    "name": ".NET Core Launch (web)",
+                    "type": "coreclr",
+                    "request": "launch",
+                    "preLaunchTask": "build",
+                    "program": "${workspaceFolder}/Web/bin/Debug/net8.0/Web.dll",
+                    "cwd": "${workspaceFolder}/Web",
+                    "console": "integratedTerminal",
+                    "stopAtEntry": false,
+                    "serverReadyAction": {
+                        "action": "openExternally",
+                        "pattern": "\\bNow listening on:\\s+(https?://\\S+)"
+                    },
+                    "env": { "ASPNETCORE_ENVIRONMENT": "Development" },
+                    "launchSettingsProfile": "https",
+                    "sourceFileMap": { "/Views": "${workspaceFolder}/Views" }
+                }),
+                serde_json::json!({
+                    "request": "launch",
+                    "buildProject": true,
+                    "program": "${workspaceFolder}/Web/bin/Debug/net8.0/Web.dll",
+                    "cwd": "${workspaceFolder}/Web",
+                    "stopAtEntry": false,
+                    "serverReadyAction": {
+                        "action": "openExternally",
+                        "pattern": "\\bNow listening on:\\s+(https?://\\S+)"
+                    },
+                    "env": { "ASPNETCORE_ENVIRONMENT": "Development" },
+                    "launchProfile": "https",
+                    "sourceFileMap": { "/Views": "${workspaceFolder}/Views" }
+                }),
+            ),
+            // Attaching cannot build first, and unsupported keys are dropped
+            (
+                serde_json::json!({
+                    "name": ".NET Core Attach",
+                    "type": "coreclr",
+                    "request": "attach",
+                    "preLaunchTask": "build",
+                    "processId": "${command:pickProcess}",
+                    "justMyCode": false,
+                    "requireExactSource": false,
+                    "logging": { "moduleLoad": false }
+                }),
+                serde_json::json!({
+                    "request": "attach",
+                    "processId": "${command:pickProcess}",
+                    "justMyCode": false
+                }),
+            ),
+            // Remote debugging over SSH keeps the pipe transport
+            (
+                serde_json::json!({
+                    "name": "Remote",
+                    "type": "coreclr",
+                    "request": "launch",
+                    "program": "/app/App.dll",
+                    "pipeTransport": {
+                        "pipeProgram": "ssh",
+                        "pipeArgs": ["-T", "pi@raspberrypi"],
+                        "debuggerPath": "~/netcoredbg/netcoredbg"
+                    },
+                    "internalConsoleOptions": "openOnSessionStart",
+                    "postDebugTask": "stop"
+                }),
+                serde_json::json!({
+                    "request": "launch",
+                    "program": "/app/App.dll",
+                    "pipeTransport": {
+                        "pipeProgram": "ssh",
+                        "pipeArgs": ["-T", "pi@raspberrypi"],
+                        "debuggerPath": "~/netcoredbg/netcoredbg"
+                    }
+                }),
+            ),
+            // Explicit native keys win over the VS Code ones
+            (
+                serde_json::json!({
+                    "type": "coreclr",
+                    "request": "launch",
+                    "preLaunchTask": "build",
+                    "buildProject": false,
+                    "launchSettingsProfile": "http",
+                    "launchProfile": "https"
+                }),
+                serde_json::json!({
+                    "request": "launch",
+                    "buildProject": false,
+                    "launchProfile": "https"
+                }),
+            ),
+        ];
+        for (vscode, expected) in cases {
+            assert_eq!(translated(vscode.clone()), expected, "config: {vscode}");
+        }
+    }
+
+    #[test]
+    fn strips_jsonc_comments_and_trailing_commas() {
+        let content = "\u{feff}{\n  // comment\n  \"url\": \"http://localhost\", /* block */\n  \"list\": [1, 2,],\n  \"text\": \"a, ]\\\" // not a comment\",\n}";
+        assert_eq!(
+            serde_json::from_str::<Value>(&strip_jsonc(content)).unwrap(),
+            serde_json::json!({
+                "url": "http://localhost",
+                "list": [1, 2],
+                "text": "a, ]\" // not a comment"
+            })
+        );
+    }
+
+    #[test]
+    fn imports_named_coreclr_configurations() {
+        let dir = TempDir::new();
+        dir.write(
+            LAUNCH_JSON_FILE,
+            r#"{
+                "version": "0.2.0",
+                "configurations": [
+                    { "name": "App", "type": "coreclr", "request": "launch", "program": "App.dll" },
+                    { "name": "Args", "type": "coreclr", "request": "launch", "args": ["${input:args}"] },
+                    { "name": "Node", "type": "node", "request": "launch" },
+                ],
+                "compounds": [{ "name": "All", "configurations": ["App", "Node"] }]
+            }"#,
+        );
+        let root = dir.path().to_str().unwrap();
+
+        let config = import_launch_json(root, "App").unwrap();
+        assert_eq!(config.get("program"), Some(&Value::from("App.dll")));
+
+        let error = import_launch_json(root, "Args").unwrap_err();
+        assert!(error.contains("'args'"), "{error}");
+        let error = import_launch_json(root, "Node").unwrap_err();
+        assert!(error.contains("'type'"), "{error}");
+        let error = import_launch_json(root, "All").unwrap_err();
+        assert!(error.contains("compound configuration"), "{error}");
+        let error = import_launch_json(root, "Missing").unwrap_err();
+        assert!(
+            error.contains("coreclr configurations: 'App', 'Args'"),
+            "{error}"
+        );
+    }
+}
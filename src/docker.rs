@@ -0,0 +1,90 @@
+use crate::logger::{Logger, ScopedLogger};
+use crate::PipeTransport;
+use serde::{Deserialize, Serialize};
+use zed_extension_api::process::Command;
+use zed_extension_api::serde_json::{Map, Value};
+
+const LOG: ScopedLogger = Logger::scoped("docker");
+
+/// Directories .NET images commonly build or publish the app in, mapped to the worktree when
+/// no `sourceFileMap` is configured
+const DEFAULT_SOURCE_ROOTS: [&str; 2] = ["/app", "/src"];
+
+/// A container whose .NET process is attached to by running netcoredbg inside it with
+/// `docker exec`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DockerTarget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<String>,
+    /// Path of netcoredbg inside the container
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debugger_path: Option<String>,
+}
+
+impl DockerTarget {
+    /// The pipe transport running netcoredbg in the container, after checking that the
+    /// container is running
+    pub fn pipe_transport(&self) -> Result<PipeTransport, String> {
+        let container = match (&self.container_id, &self.container_name) {
+            (Some(_), Some(_)) => {
+                return Err(
+                    "'docker' takes either 'containerId' or 'containerName', not both".to_string(),
+                )
+            }
+            (Some(container), None) | (None, Some(container)) => container.clone(),
+            (None, None) => {
+                return Err("'docker' requires 'containerId' or 'containerName' of the container to attach in (see `docker ps`)".to_string())
+            }
+        };
+        if self.debugger_path.is_none() {
+            return Err("'docker' requires 'debuggerPath', the path of netcoredbg inside the container (e.g. \"/vsdbg/netcoredbg\")".to_string());
+        }
+        Self::check_running(&container)?;
+        LOG.info(&format!("Attaching in running container '{}'", container));
+
+        Ok(PipeTransport {
+            pipe_program: Some("docker".to_string()),
+            pipe_args: vec!["exec".to_string(), "-i".to_string(), container],
+            debugger_path: self.debugger_path.clone(),
+            pipe_cwd: None,
+            quote_args: Some(false),
+        })
+    }
+
+    fn check_running(container: &str) -> Result<(), String> {
+        let output = Command::new("docker")
+            .args(["inspect", "--format", "{{.State.Running}}", container])
+            .output()
+            .map_err(|e| format!("Failed to run docker: {}. Is Docker installed?", e))?;
+        if output.status != Some(0) {
+            return Err(format!(
+                "Container '{}' was not found: {}",
+                container,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        match String::from_utf8_lossy(&output.stdout).trim() {
+            "true" => Ok(()),
+            _ => Err(format!(
+                "Container '{}' is not running. Start it with `docker start {}`.",
+                container, container
+            )),
+        }
+    }
+
+    /// Maps the usual source directories of .NET images to the worktree
+    pub fn default_source_file_map() -> Map<String, Value> {
+        DEFAULT_SOURCE_ROOTS
+            .iter()
+            .map(|root| {
+                (
+                    root.to_string(),
+                    Value::String("${workspaceFolder}".to_string()),
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,217 @@
+use crate::logger::{Logger, ScopedLogger};
+use crate::platform;
+use crate::version::compare_versions;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::path::Path;
+use zed_extension_api::process::Command;
+use zed_extension_api::{self as zed, serde_json, Worktree};
+
+const LOG: ScopedLogger = Logger::scoped("dotnet");
+
+/// Where .NET runtimes and SDKs can be downloaded from
+const DOWNLOAD_URL: &str = "https://dotnet.microsoft.com/download/dotnet";
+
+/// A located `dotnet` host and the shared runtimes it reports
+#[derive(Debug, Clone)]
+pub struct Dotnet {
+    pub path: String,
+    /// `(framework name, version)` pairs, such as `("Microsoft.NETCore.App", "8.0.1")`
+    runtimes: Vec<(String, String)>,
+}
+
+/// A shared framework an application needs, as listed in its `runtimeconfig.json`
+#[derive(Deserialize, Debug)]
+struct FrameworkReference {
+    name: String,
+    version: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeOptions {
+    #[serde(default)]
+    framework: Option<FrameworkReference>,
+    #[serde(default)]
+    frameworks: Vec<FrameworkReference>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeConfig {
+    #[serde(default)]
+    runtime_options: RuntimeOptions,
+}
+
+impl Dotnet {
+    /// Locates `dotnet` through `DOTNET_ROOT`, `PATH` and the default install locations of the
+    /// platform, and lists its runtimes with `dotnet --list-runtimes`
+    pub fn locate(worktree: &Worktree) -> Result<Self, String> {
+        let env: std::collections::HashMap<String, String> =
+            worktree.shell_env().into_iter().collect();
+        let (executable, default_dirs): (&str, &[&str]) = match platform::os() {
+            zed::Os::Windows => (
+                "dotnet.exe",
+                &[r"C:\Program Files\dotnet", r"C:\Program Files (x86)\dotnet"],
+            ),
+            zed::Os::Mac => (
+                "dotnet",
+                &[
+                    "/usr/local/share/dotnet",
+                    "/opt/homebrew/opt/dotnet/libexec",
+                ],
+            ),
+            zed::Os::Linux => (
+                "dotnet",
+                &[
+                    "/usr/share/dotnet",
+                    "/usr/lib/dotnet",
+                    "/usr/local/share/dotnet",
+                ],
+            ),
+        };
+
+        let mut candidates: Vec<String> = Vec::new();
+        if let Some(root) = env.get("DOTNET_ROOT") {
+            candidates.push(
+                Path::new(root)
+                    .join(executable)
+                    .to_string_lossy()
+                    .into_owned(),
+            );
+        }
+        let on_path = worktree.which("dotnet");
+        let home_dir = env
+            .get("HOME")
+            .or_else(|| env.get("USERPROFILE"))
+            .map(|home| {
+                Path::new(home)
+                    .join(".dotnet")
+                    .to_string_lossy()
+                    .into_owned()
+            });
+        candidates.extend(
+            default_dirs
+                .iter()
+                .map(|dir| dir.to_string())
+                .chain(home_dir)
+                .map(|dir| {
+                    Path::new(&dir)
+                        .join(executable)
+                        .to_string_lossy()
+                        .into_owned()
+                }),
+        );
+
+        let path = candidates
+            .iter()
+            .find(|candidate| Path::new(candidate).is_file())
+            .cloned()
+            .or(on_path)
+            .ok_or_else(|| {
+                format!(
+                    "The .NET runtime was not found on PATH, in DOTNET_ROOT or in its default install locations ({}). Install it from {}, or set DOTNET_ROOT.",
+                    default_dirs.join(", "),
+                    DOWNLOAD_URL
+                )
+            })?;
+
+        let dotnet = Self {
+            path,
+            runtimes: Vec::new(),
+        };
+        let output = dotnet
+            .command(&[])
+            .arg("--list-runtimes")
+            .output()
+            .map_err(|e| format!("Failed to run {} --list-runtimes: {}", dotnet.path, e))?;
+        let runtimes = Self::parse_runtimes(&String::from_utf8_lossy(&output.stdout));
+        LOG.info(&format!(
+            "Found dotnet at {} with runtimes: {}",
+            dotnet.path,
+            runtimes
+                .iter()
+                .map(|(name, version)| format!("{} {}", name, version))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        Ok(Self { runtimes, ..dotnet })
+    }
+
+    /// A command running this `dotnet` host with `env`
+    pub fn command(&self, env: &[(String, String)]) -> Command {
+        let path = Path::new(&self.path);
+        platform::command_in(path.parent().unwrap_or(Path::new("")), "dotnet", env)
+    }
+
+    /// Parses `dotnet --list-runtimes` lines such as
+    /// `Microsoft.NETCore.App 8.0.1 [/usr/share/dotnet/shared/Microsoft.NETCore.App]`
+    fn parse_runtimes(output: &str) -> Vec<(String, String)> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                Some((parts.next()?.to_string(), parts.next()?.to_string()))
+            })
+            .collect()
+    }
+
+    /// Checks that the shared frameworks listed in the `runtimeconfig.json` next to `assembly`
+    /// are installed in a version the app can run on: the same major version, at least the
+    /// requested one. Self-contained apps and assemblies without a runtime config pass.
+    pub fn check_runtimes(&self, assembly: &Path) -> Result<(), String> {
+        let runtime_config = assembly.with_extension("runtimeconfig.json");
+        let Ok(content) = std::fs::read_to_string(&runtime_config) else {
+            LOG.debug(&format!(
+                "No {} found; skipping the runtime check",
+                runtime_config.display()
+            ));
+            return Ok(());
+        };
+        let config: RuntimeConfig = serde_json::from_str(&content).map_err(|e| {
+            format!(
+                "Invalid runtime config '{}': {}",
+                runtime_config.display(),
+                e
+            )
+        })?;
+        let options = config.runtime_options;
+
+        for required in options.framework.iter().chain(&options.frameworks) {
+            let major = required.version.split('.').next().unwrap_or_default();
+            let satisfied = self.runtimes.iter().any(|(name, version)| {
+                name == &required.name
+                    && version.split('.').next() == Some(major)
+                    && compare_versions(version, &required.version) != Ordering::Less
+            });
+            if !satisfied {
+                let installed: Vec<&str> = self
+                    .runtimes
+                    .iter()
+                    .filter(|(name, _)| name == &required.name)
+                    .map(|(_, version)| version.as_str())
+                    .collect();
+                let channel = required
+                    .version
+                    .split('.')
+                    .take(2)
+                    .collect::<Vec<_>>()
+                    .join(".");
+                return Err(format!(
+                    "{} requires the {} {} runtime, which is not installed (installed: {}). Download it from {}/{}",
+                    assembly.display(),
+                    required.name,
+                    required.version,
+                    if installed.is_empty() {
+                        "none".to_string()
+                    } else {
+                        installed.join(", ")
+                    },
+                    DOWNLOAD_URL,
+                    channel
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,413 @@
+use crate::logger::{Logger, ScopedLogger};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use zed_extension_api::serde_json::{Map, Value};
+
+const LOG: ScopedLogger = Logger::scoped("environment");
+
+/// Variables naming the hosting environment (`Development`, `Production`, ...) of ASP.NET Core
+/// and generic host applications
+const HOSTING_ENVIRONMENT_VARS: [&str; 2] = ["ASPNETCORE_ENVIRONMENT", "DOTNET_ENVIRONMENT"];
+
+/// Hosting environment of ASP.NET Core projects launched without one
+pub const DEFAULT_HOSTING_ENVIRONMENT: &str = "Development";
+
+/// Runtime settings making the JIT compile debuggable code: minimal optimizations, and no
+/// precompiled ReadyToRun or NGen images, which are optimized
+const UNOPTIMIZED_JIT_VARS: [(&str, &str); 3] = [
+    ("COMPlus_JITMinOpts", "1"),
+    ("COMPlus_ReadyToRun", "0"),
+    ("COMPlus_ZapDisable", "1"),
+];
+
+/// Environment variables of the debuggee. Sources are applied in increasing precedence:
+/// the inherited environment, user secrets, `launchSettings.json`, `envFile`, the
+/// configuration's `environment` and `suppressJITOptimizations`, and finally its `env`, so
+/// that later sources override earlier ones.
+pub struct Environment {
+    inherited: HashMap<String, String>,
+    /// Variables set by the applied sources; `None` unsets a variable
+    vars: BTreeMap<String, Option<String>>,
+}
+
+impl Environment {
+    pub fn new(inherited: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self {
+            inherited: inherited.into_iter().collect(),
+            vars: BTreeMap::new(),
+        }
+    }
+
+    /// Applies `vars` on top of the variables set so far
+    pub fn apply(&mut self, vars: impl IntoIterator<Item = (String, Option<String>)>) {
+        self.vars.extend(vars);
+    }
+
+    /// Whether `key` is set, by the applied sources or else by the inherited environment
+    pub fn is_set(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The value of `key`, from the applied sources or else the inherited environment
+    pub fn get(&self, key: &str) -> Option<&str> {
+        match self.vars.get(key) {
+            Some(value) => value.as_deref(),
+            None => self.inherited.get(key).map(String::as_str),
+        }
+    }
+
+    /// Sets the hosting environment to [`DEFAULT_HOSTING_ENVIRONMENT`] unless a source or the
+    /// inherited environment sets one. Returns whether it did.
+    pub fn apply_default_hosting(&mut self) -> bool {
+        if HOSTING_ENVIRONMENT_VARS.iter().any(|key| self.is_set(key)) {
+            return false;
+        }
+        self.apply(Self::hosting_vars(DEFAULT_HOSTING_ENVIRONMENT));
+        true
+    }
+
+    /// The variables setting the hosting environment to `name`
+    pub fn hosting_vars(name: &str) -> Vec<(String, Option<String>)> {
+        HOSTING_ENVIRONMENT_VARS
+            .iter()
+            .map(|key| (key.to_string(), Some(name.to_string())))
+            .collect()
+    }
+
+    /// The variables suppressing JIT optimizations in the launched process
+    pub fn unoptimized_jit_vars() -> Vec<(String, Option<String>)> {
+        UNOPTIMIZED_JIT_VARS
+            .iter()
+            .map(|(key, value)| (key.to_string(), Some(value.to_string())))
+            .collect()
+    }
+
+    /// Converts the configuration's `env` object, coercing numbers and booleans to strings
+    /// with a warning. `null` unsets the variable.
+    pub fn config_vars(env: &Map<String, Value>) -> Result<Vec<(String, Option<String>)>, String> {
+        env.iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::String(value) => Some(value.clone()),
+                    Value::Null => None,
+                    Value::Number(_) | Value::Bool(_) => {
+                        LOG.warn(&format!(
+                            "env value of '{}' is not a string; converting {} to \"{}\"",
+                            key, value, value
+                        ));
+                        Some(value.to_string())
+                    }
+                    Value::Array(_) | Value::Object(_) => {
+                        return Err(format!(
+                            "env value of '{}' must be a string, got {}",
+                            key, value
+                        ))
+                    }
+                };
+                Ok((key.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Reads the `KEY=VALUE` lines of a `.env` file
+    pub fn env_file_vars(path: &Path) -> Result<Vec<(String, Option<String>)>, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read envFile '{}': {}", path.display(), e))?;
+        Self::parse_env_file(&content)
+            .map_err(|e| format!("Invalid envFile '{}': {}", path.display(), e))
+    }
+
+    /// Parses `.env` content: `KEY=VALUE` lines with optional `export ` prefixes, `#` comments,
+    /// single-quoted literal values and double-quoted values with `\n`, `\r`, `\t`, `\"` and
+    /// `\\` escapes. A leading byte order mark and CRLF line endings are accepted.
+    fn parse_env_file(content: &str) -> Result<Vec<(String, Option<String>)>, String> {
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let mut vars = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected KEY=VALUE", line_number))?;
+            let key = key.trim();
+            if key.is_empty()
+                || !key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.'))
+            {
+                return Err(format!(
+                    "line {}: invalid variable name '{}'",
+                    line_number, key
+                ));
+            }
+            let value = Self::parse_env_value(value.trim())
+                .map_err(|e| format!("line {}: {}", line_number, e))?;
+            vars.push((key.to_string(), Some(value)));
+        }
+        Ok(vars)
+    }
+
+    fn parse_env_value(value: &str) -> Result<String, String> {
+        let (parsed, rest) = if let Some(quoted) = value.strip_prefix('\'') {
+            let end = quoted
+                .find('\'')
+                .ok_or_else(|| "unterminated single-quoted value".to_string())?;
+            (quoted[..end].to_string(), &quoted[end + 1..])
+        } else if let Some(quoted) = value.strip_prefix('"') {
+            let mut parsed = String::new();
+            let mut chars = quoted.char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((index, '"')) => break index,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => parsed.push('\n'),
+                        Some((_, 'r')) => parsed.push('\r'),
+                        Some((_, 't')) => parsed.push('\t'),
+                        Some((_, c @ ('"' | '\\'))) => parsed.push(c),
+                        Some((_, c)) => {
+                            parsed.push('\\');
+                            parsed.push(c);
+                        }
+                        None => return Err("unterminated double-quoted value".to_string()),
+                    },
+                    Some((_, c)) => parsed.push(c),
+                    None => return Err("unterminated double-quoted value".to_string()),
+                }
+            };
+            (parsed, &quoted[end + 1..])
+        } else {
+            // An unquoted value ends at a comment preceded by whitespace
+            let end = value
+                .char_indices()
+                .find(|&(index, c)| c == '#' && value[..index].ends_with(char::is_whitespace))
+                .map_or(value.len(), |(index, _)| index);
+            return Ok(value[..end].trim_end().to_string());
+        };
+
+        let rest = rest.trim_start();
+        if !rest.is_empty() && !rest.starts_with('#') {
+            return Err(format!("unexpected '{}' after quoted value", rest));
+        }
+        Ok(parsed)
+    }
+
+    /// The variables to add to the debuggee's environment. netcoredbg can only set variables,
+    /// so an unset inherited variable is set to an empty value instead.
+    pub fn into_vars(self) -> BTreeMap<String, String> {
+        self.vars
+            .into_iter()
+            .filter_map(|(key, value)| match value {
+                Some(value) => Some((key, value)),
+                None if self.inherited.contains_key(&key) => {
+                    LOG.info(&format!(
+                        "Inherited variable '{}' cannot be unset; setting it to an empty value",
+                        key
+                    ));
+                    Some((key, String::new()))
+                }
+                None => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launch_settings::LaunchProfile;
+    use crate::test_support::TempDir;
+    use zed_extension_api::serde_json;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, Option<String>)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), Some(value.to_string())))
+            .collect()
+    }
+
+    /// Applies the sources in the order `dap_binary` does
+    fn merged(
+        profile_env: Value,
+        environment: Option<&str>,
+        env: Value,
+    ) -> (BTreeMap<String, String>, bool) {
+        let dir = TempDir::new();
+        dir.write(
+            "Properties/launchSettings.json",
+            serde_json::json!({
+                "profiles": {
+                    "https": {
+                        "commandName": "Project",
+                        "applicationUrl": "https://localhost:5001",
+                        "environmentVariables": profile_env
+                    }
+                }
+            })
+            .to_string(),
+        );
+        let profile = LaunchProfile::load(dir.path(), None).unwrap().unwrap();
+
+        let mut merged = Environment::new([("PATH".to_string(), "/usr/bin".to_string())]);
+        merged.apply(vars(&[("ConnectionStrings__Db", "from-secrets")]));
+        merged.apply(profile.env_vars().unwrap());
+        if let Some(name) = environment {
+            merged.apply(Environment::hosting_vars(name));
+        }
+        let Value::Object(env) = env else {
+            panic!("env is an object");
+        };
+        merged.apply(Environment::config_vars(&env).unwrap());
+        let defaulted = merged.apply_default_hosting();
+        (merged.into_vars(), defaulted)
+    }
+
+    #[test]
+    fn merges_hosting_environment_sources_in_order() {
+        let no_variables = serde_json::json!({});
+        let cases = [
+            // Nothing set: Web SDK projects default to Development
+            (
+                no_variables.clone(),
+                None,
+                serde_json::json!({}),
+                ("Development", "Development"),
+                true,
+            ),
+            // launchSettings.json environment variables apply as they are
+            (
+                serde_json::json!({"ASPNETCORE_ENVIRONMENT": "Staging"}),
+                None,
+                serde_json::json!({}),
+                ("Staging", ""),
+                false,
+            ),
+            // The `environment` key overrides launchSettings.json
+            (
+                serde_json::json!({"ASPNETCORE_ENVIRONMENT": "Staging"}),
+                Some("Production"),
+                serde_json::json!({}),
+                ("Production", "Production"),
+                false,
+            ),
+            // ...and explicit `env` entries are not clobbered by it
+            (
+                no_variables,
+                Some("Production"),
+                serde_json::json!({"DOTNET_ENVIRONMENT": "Test"}),
+                ("Production", "Test"),
+                false,
+            ),
+        ];
+        for (profile_env, environment, env, (aspnetcore, dotnet), defaulted) in cases {
+            let (vars, applied_default) = merged(profile_env, environment, env);
+            let get = |key: &str| vars.get(key).map_or("", String::as_str);
+            assert_eq!(
+                (get("ASPNETCORE_ENVIRONMENT"), get("DOTNET_ENVIRONMENT")),
+                (aspnetcore, dotnet),
+                "environment: {environment:?}"
+            );
+            assert_eq!(applied_default, defaulted);
+            assert_eq!(get("ASPNETCORE_URLS"), "https://localhost:5001");
+            assert_eq!(get("ConnectionStrings__Db"), "from-secrets");
+            // Inherited variables are not repeated
+            assert_eq!(get("PATH"), "");
+        }
+    }
+
+    #[test]
+    fn parses_env_files() {
+        let content = concat!(
+            "\u{feff}# Local settings\r\n",
+            "\r\n",
+            "PLAIN=value\r\n",
+            "export EXPORTED=yes\r\n",
+            "  SPACED = padded value  \r\n",
+            "COMMENTED=value # trailing comment\r\n",
+            "HASH=abc#def\r\n",
+            "EMPTY=\r\n",
+            "SINGLE='literal \\n $HOME # not a comment'\r\n",
+            "DOUBLE=\"line\\nnext \\\"quoted\\\" \\\\ \\x\" # comment\r\n",
+            "NESTED=\"it's 'fine'\"\r\n",
+            "URL=postgres://user:p=ss@db/app\r\n",
+            "Logging.LogLevel=Debug",
+        );
+        assert_eq!(
+            Environment::parse_env_file(content).unwrap(),
+            vars(&[
+                ("PLAIN", "value"),
+                ("EXPORTED", "yes"),
+                ("SPACED", "padded value"),
+                ("COMMENTED", "value"),
+                ("HASH", "abc#def"),
+                ("EMPTY", ""),
+                ("SINGLE", "literal \\n $HOME # not a comment"),
+                ("DOUBLE", "line\nnext \"quoted\" \\ \\x"),
+                ("NESTED", "it's 'fine'"),
+                ("URL", "postgres://user:p=ss@db/app"),
+                ("Logging.LogLevel", "Debug"),
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_env_file_errors_with_line_numbers() {
+        let cases = [
+            ("A=1\nnot a variable", "line 2: expected KEY=VALUE"),
+            ("\n\nexport =1", "line 3: invalid variable name ''"),
+            ("MY-VAR=1", "line 1: invalid variable name 'MY-VAR'"),
+            ("A='open", "line 1: unterminated single-quoted value"),
+            ("A=\"open\\\"", "line 1: unterminated double-quoted value"),
+            ("A=\"x\" y", "line 1: unexpected 'y' after quoted value"),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(
+                Environment::parse_env_file(content).unwrap_err(),
+                expected,
+                "content: {content:?}"
+            );
+        }
+
+        let dir = TempDir::new();
+        let path = dir.write(".env", "A='open");
+        let error = Environment::env_file_vars(&path).unwrap_err();
+        assert!(error.starts_with("Invalid envFile"), "{error}");
+        let error = Environment::env_file_vars(&dir.path().join("missing.env")).unwrap_err();
+        assert!(error.starts_with("Failed to read envFile"), "{error}");
+    }
+
+    #[test]
+    fn keeps_an_inherited_hosting_environment() {
+        let mut environment =
+            Environment::new([("DOTNET_ENVIRONMENT".to_string(), "Staging".to_string())]);
+        assert!(!environment.apply_default_hosting());
+        assert!(environment.into_vars().is_empty());
+    }
+
+    #[test]
+    fn unsets_variables_with_null() {
+        let mut environment = Environment::new([("DEBUG".to_string(), "1".to_string())]);
+        environment.apply(vars(&[("VERBOSE", "1")]));
+        let Value::Object(env) = serde_json::json!({
+            "DEBUG": null,
+            "VERBOSE": null,
+            "PORT": 5001,
+            "TRACE": true
+        }) else {
+            unreachable!()
+        };
+        environment.apply(Environment::config_vars(&env).unwrap());
+        assert!(!environment.is_set("DEBUG"));
+        assert_eq!(
+            environment.into_vars(),
+            BTreeMap::from([
+                ("DEBUG".to_string(), String::new()),
+                ("PORT".to_string(), "5001".to_string()),
+                ("TRACE".to_string(), "true".to_string()),
+            ])
+        );
+    }
+}
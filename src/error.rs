@@ -0,0 +1,198 @@
+use crate::settings::PathScope;
+use std::fmt;
+use std::time::Duration;
+
+/// Errors raised while resolving, downloading or installing the netcoredbg binary
+#[derive(Debug)]
+pub enum BinaryManagerError {
+    /// Release metadata could not be fetched from GitHub
+    ReleaseFetch {
+        repo: String,
+        /// Requested tag, `None` for the latest release
+        tag: Option<String>,
+        message: String,
+        /// Tags the repository does publish, listed when a specific tag was requested
+        available_tags: Vec<String>,
+    },
+    /// netcoredbg releases are not published for this platform at all
+    UnsupportedPlatform { reason: String },
+    /// The release has no asset for this platform
+    AssetNotFound {
+        repo: String,
+        release: String,
+        wanted: String,
+        available: Vec<String>,
+        /// Additional explanation, e.g. about musl-based systems
+        note: Option<String>,
+    },
+    /// The asset at a download URL is neither a supported archive nor a bare executable
+    UnsupportedAsset { url: String },
+    /// A release lookup or download kept failing until the `timeout_secs` budget ran out
+    Timeout {
+        operation: String,
+        elapsed: Duration,
+        /// Error of the last attempt
+        message: String,
+    },
+    /// Downloading the archive or its checksum file failed
+    Download { url: String, message: String },
+    /// The archive could not be unpacked into a usable install
+    Extraction(String),
+    /// The downloaded executable does not match the expected SHA256 digest
+    ChecksumMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+    /// The installed executable does not run
+    VerificationFailed { version: String, message: String },
+    /// The executable exits with an error when started
+    LaunchFailed {
+        path: String,
+        message: String,
+        /// Last lines the executable wrote to stderr
+        stderr: Vec<String>,
+    },
+    /// The install directory lacks the space needed for the download
+    InsufficientDiskSpace {
+        version: String,
+        dir: String,
+        required_mb: u64,
+        available_mb: u64,
+    },
+    /// The netcoredbg binary path from the Zed settings is unusable
+    UserPathInvalid {
+        scope: PathScope,
+        path: String,
+        message: String,
+    },
+    /// A value in the `netcoredbg` adapter settings is invalid
+    InvalidSetting {
+        setting: &'static str,
+        message: String,
+    },
+    /// A file system operation on the install directory failed
+    FileSystem(String),
+}
+
+impl fmt::Display for BinaryManagerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReleaseFetch {
+                repo,
+                tag: Some(tag),
+                message,
+                available_tags,
+            } => write!(
+                f,
+                "Failed to fetch release '{}' from {}: {}. Available tags: [{}]",
+                tag,
+                repo,
+                message,
+                available_tags.join(", ")
+            ),
+            Self::ReleaseFetch {
+                repo,
+                tag: None,
+                message,
+                ..
+            } => write!(f, "Failed to fetch latest release from {}: {}", repo, message),
+            Self::UnsupportedPlatform { reason } => {
+                write!(f, "This platform is not supported: {}", reason)
+            }
+            Self::AssetNotFound {
+                repo,
+                release,
+                wanted,
+                available,
+                note,
+            } => {
+                write!(
+                    f,
+                    "No compatible asset found for platform in {} release {}. Looking for: '{}'. Available assets: [{}]",
+                    repo,
+                    release,
+                    wanted,
+                    available.join(", ")
+                )?;
+                if let Some(note) = note {
+                    write!(f, ". {}", note)?;
+                }
+                Ok(())
+            }
+            Self::UnsupportedAsset { url } => write!(
+                f,
+                "Unsupported file type for asset {}, expected a .zip, .tar.gz or .tar.xz archive or a bare executable",
+                url
+            ),
+            Self::Timeout {
+                operation,
+                elapsed,
+                message,
+            } => write!(
+                f,
+                "netcoredbg {} timed out after {:.1}s: {}",
+                operation,
+                elapsed.as_secs_f64(),
+                message
+            ),
+            Self::Download { url, message } => {
+                write!(f, "Failed to download {}: {}", url, message)
+            }
+            Self::Extraction(message) => write!(f, "Failed to install netcoredbg: {}", message),
+            Self::ChecksumMismatch {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Checksum mismatch for {}: expected {}, actual {}",
+                path, expected, actual
+            ),
+            Self::VerificationFailed { version, message } => write!(
+                f,
+                "Installed netcoredbg {} does not run: {}",
+                version, message
+            ),
+            Self::LaunchFailed {
+                path,
+                message,
+                stderr,
+            } => {
+                write!(f, "netcoredbg at {} fails to start: {}", path, message)?;
+                if !stderr.is_empty() {
+                    write!(f, "\nLast stderr lines:")?;
+                    for line in stderr {
+                        write!(f, "\n  {}", line)?;
+                    }
+                }
+                Ok(())
+            }
+            Self::InsufficientDiskSpace {
+                version,
+                dir,
+                required_mb,
+                available_mb,
+            } => write!(
+                f,
+                "Not enough disk space to install netcoredbg {}: need ~{} MB free in {}, only {} MB available",
+                version, required_mb, dir, available_mb
+            ),
+            Self::UserPathInvalid {
+                scope,
+                path,
+                message,
+            } => write!(
+                f,
+                "Invalid netcoredbg binary path '{}' from {}: {}",
+                path, scope, message
+            ),
+            Self::InvalidSetting { setting, message } => {
+                write!(f, "Invalid '{}' setting: {}", setting, message)
+            }
+            Self::FileSystem(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for BinaryManagerError {}
@@ -0,0 +1,127 @@
+use crate::environment::Environment;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::path::Path;
+use zed_extension_api::serde_json::{self, Map, Value};
+
+/// Location of the launch settings relative to the project directory
+const LAUNCH_SETTINGS_FILE: &str = "Properties/launchSettings.json";
+
+/// `commandName` of the profiles that run the project itself
+const PROJECT_COMMAND: &str = "Project";
+
+/// A profile of a project's `Properties/launchSettings.json`
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchProfile {
+    #[serde(skip)]
+    pub name: String,
+    #[serde(default)]
+    command_name: Option<String>,
+    #[serde(default)]
+    environment_variables: Map<String, Value>,
+    #[serde(default)]
+    application_url: Option<String>,
+    #[serde(default)]
+    pub command_line_args: Option<String>,
+    #[serde(default)]
+    pub working_directory: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LaunchSettings {
+    #[serde(default)]
+    profiles: Profiles,
+}
+
+/// Profiles in the order they appear in the file, which decides the default profile
+#[derive(Default)]
+struct Profiles(Vec<LaunchProfile>);
+
+impl<'de> Deserialize<'de> for Profiles {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ProfilesVisitor;
+
+        impl<'de> Visitor<'de> for ProfilesVisitor {
+            type Value = Profiles;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("an object of launch profiles")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Profiles, A::Error> {
+                let mut profiles = Vec::new();
+                while let Some((name, mut profile)) = map.next_entry::<String, LaunchProfile>()? {
+                    profile.name = name;
+                    profiles.push(profile);
+                }
+                Ok(Profiles(profiles))
+            }
+        }
+
+        deserializer.deserialize_map(ProfilesVisitor)
+    }
+}
+
+impl LaunchProfile {
+    /// Reads the profile named `name` from the launch settings of the project in
+    /// `project_dir`, or else the first profile running the project. Returns `None` when no
+    /// profile was requested and the project has no launch settings or no such profile.
+    pub fn load(project_dir: &Path, name: Option<&str>) -> Result<Option<Self>, String> {
+        let path = project_dir.join(LAUNCH_SETTINGS_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && name.is_none() => {
+                return Ok(None)
+            }
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read launch settings '{}': {}",
+                    path.display(),
+                    e
+                ))
+            }
+        };
+        let content = content.strip_prefix('\u{feff}').unwrap_or(&content);
+        let settings: LaunchSettings = serde_json::from_str(content)
+            .map_err(|e| format!("Invalid launch settings '{}': {}", path.display(), e))?;
+        let profiles = settings.profiles.0;
+
+        let Some(name) = name else {
+            return Ok(profiles
+                .into_iter()
+                .find(|profile| profile.command_name.as_deref() == Some(PROJECT_COMMAND)));
+        };
+        let names: Vec<String> = profiles
+            .iter()
+            .map(|profile| format!("'{}'", profile.name))
+            .collect();
+        profiles
+            .into_iter()
+            .find(|profile| profile.name == name)
+            .map(Some)
+            .ok_or_else(|| {
+                format!(
+                    "Launch profile '{}' not found in '{}'. Profiles: {}",
+                    name,
+                    path.display(),
+                    if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                )
+            })
+    }
+
+    /// The profile's environment variables, with `applicationUrl` exported as `ASPNETCORE_URLS`
+    pub fn env_vars(&self) -> Result<Vec<(String, Option<String>)>, String> {
+        let urls = self
+            .application_url
+            .iter()
+            .map(|urls| ("ASPNETCORE_URLS".to_string(), Some(urls.clone())));
+        Ok(urls
+            .chain(Environment::config_vars(&self.environment_variables)?)
+            .collect())
+    }
+}
@@ -1,36 +1,225 @@
+mod args;
+mod assembly;
 mod binary_manager;
+mod compat;
+mod docker;
+mod dotnet;
+mod environment;
+mod error;
+mod launch_settings;
+mod locator;
 mod logger;
+mod paths;
+mod platform;
+mod ports;
+mod processes;
+mod project;
+mod settings;
+mod sha256;
+#[cfg(test)]
+mod test_support;
+mod user_secrets;
+mod validation;
+mod variables;
+mod version;
+mod wsl;
 
-use binary_manager::BinaryManager;
+use assembly::TargetKind;
+use binary_manager::{BinaryManager, InstalledVersion};
+use docker::DockerTarget;
+use dotnet::Dotnet;
+use environment::{Environment, DEFAULT_HOSTING_ENVIRONMENT};
+use error::BinaryManagerError;
+use launch_settings::LaunchProfile;
+use logger::{Logger, ScopedLogger};
+use processes::{WaitingProcess, PICK_PROCESS};
+use project::{ApphostMode, BuildMode, LaunchMode, Project, AUTO_PROGRAM};
 use serde::{Deserialize, Serialize};
+use settings::{AdapterSettings, PathScope};
 use std::collections::HashMap;
+use variables::Variables;
+use wsl::WslTarget;
 use zed_extension_api::serde_json::Value;
 use zed_extension_api::{
     self as zed, serde_json, DebugAdapterBinary, DebugConfig, DebugRequest, DebugScenario,
     DebugTaskDefinition, StartDebuggingRequestArguments, StartDebuggingRequestArgumentsRequest,
-    Worktree,
+    TaskTemplate, Worktree,
 };
 
-#[derive(Serialize, Deserialize, Debug)]
+const LOG: ScopedLogger = Logger::scoped("adapter");
+
+#[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct NetCoreDbgDebugConfig {
     pub request: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub program: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub args: Option<Vec<String>>,
+    /// An array of arguments, or a command line string split with shell-like quoting
+    pub args: Option<Value>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cwd: Option<String>,
-    #[serde(default)]
-    pub env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub env: serde_json::Map<String, Value>,
+    /// Maps source paths embedded in PDBs (keys) to local directories (values)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_file_map: Option<serde_json::Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol_options: Option<SymbolOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub console: Option<Console>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipe_transport: Option<PipeTransport>,
+    /// Container to attach in, through a pipe transport running `docker exec`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docker: Option<DockerTarget>,
+    /// Shorthand for a `wsl` target in the default distribution
+    #[serde(rename = "useWSL", default, skip_serializing_if = "Option::is_none")]
+    pub use_wsl: Option<bool>,
+    /// WSL distribution to debug in, through a pipe transport running `wsl.exe`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wsl: Option<WslTarget>,
+    /// Profile of the project's `Properties/launchSettings.json` to apply
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_profile: Option<String>,
+    /// Hosting environment set as both `ASPNETCORE_ENVIRONMENT` and `DOTNET_ENVIRONMENT`,
+    /// beneath the variables in `env`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// Whether the project's `dotnet user-secrets` are set as environment variables; by
+    /// default when the project has a `UserSecretsId`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_user_secrets: Option<bool>,
+    /// `.env` file whose variables are set beneath those in `env`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub stop_at_entry: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub process_id: Option<ProcessId>,
+    /// Name of the process to attach to, looked up instead of `processId`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_name: Option<String>,
+    /// Whether to wait for a new process named `processName` to start
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_for: Option<bool>,
+    /// Seconds to wait for the process with `waitFor`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub just_my_code: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enable_step_filtering: Option<bool>,
+    /// Makes the runtime compile unoptimized code, so that optimized builds show their locals
+    #[serde(
+        rename = "suppressJITOptimizations",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub suppress_jit_optimizations: Option<bool>,
+    /// Asks for stepping into library code, which turns `justMyCode` off by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_source_link: Option<bool>,
+    /// Project whose build output is launched when `program` is omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_framework: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub configuration: Option<String>,
+    /// Whether to run `dotnet build` before launching; `build` is taken by Zed's build tasks
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub build_project: BuildMode,
+    /// Whether the project's assembly or its `dotnet run` command is launched
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub launch_mode: LaunchMode,
+    /// Skips the check that the ports the program listens on are free
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_port_check: Option<bool>,
+    /// Skips the check that the program is managed code netcoredbg can debug
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_target_validation: Option<bool>,
+    /// Whether the project's apphost executable is launched instead of its assembly
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub use_apphost: ApphostMode,
+    /// Extra command line options of netcoredbg, such as `--log=file`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adapter_args: Vec<String>,
+    /// Writes netcoredbg's engine log, to the given file or next to the extension's log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine_logging: Option<EngineLogging>,
+    /// Extension-side settings controlling how the netcoredbg binary is obtained
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub netcoredbg: Option<AdapterSettings>,
+}
+
+/// Whether `value` is its type's default, which is left out of generated configurations
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// Runs netcoredbg on another machine through a program that pipes the DAP stream over its
+/// standard input and output, such as `ssh`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PipeTransport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipe_program: Option<String>,
+    #[serde(default)]
+    pub pipe_args: Vec<String>,
+    /// Path of netcoredbg on the remote machine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debugger_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipe_cwd: Option<String>,
+    /// Whether arguments of the remote command containing whitespace are quoted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_args: Option<bool>,
+}
+
+/// Where netcoredbg looks for symbol (PDB) files
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolOptions {
+    /// Directories, network shares or symbol server URLs searched for PDBs
+    #[serde(default)]
+    pub search_paths: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_microsoft_symbol_server: Option<bool>,
+    /// Directory symbols downloaded from symbol servers are cached in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_path: Option<String>,
+}
+
+/// Where the output of a launched program is shown. netcoredbg never sends the DAP
+/// `runInTerminal` request, so the terminal consoles of other adapters are not supported.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Console {
+    /// The debug console, without input
+    InternalConsole,
+}
+
+/// `console` values of other .NET debuggers that start the program in a terminal
+const TERMINAL_CONSOLES: [&str; 2] = ["integratedTerminal", "externalTerminal"];
+
+/// Either whether netcoredbg's engine log is written to its default location, or the file it
+/// is written to
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum EngineLogging {
+    Enabled(bool),
+    Path(String),
+}
+
+/// What resolving a local launch found besides the values recorded in the configuration
+struct LocalLaunch {
+    profile: Option<LaunchProfile>,
+    cwd: String,
+    /// Arguments the run command passes before those of the configuration
+    run_args: Vec<String>,
+    /// Project file of the program, when it was found
+    project: Option<std::path::PathBuf>,
 }
 
 /// Represents a process id that can be either an integer or a string (containing a number)
@@ -44,18 +233,127 @@ pub enum ProcessId {
 #[derive(Default)]
 struct NetCoreDbgExtension {
     binary_manager: BinaryManager,
+    /// The `dotnet` host, located once per Zed session
+    dotnet: Option<Dotnet>,
 }
 
 impl NetCoreDbgExtension {
     const ADAPTER_NAME: &str = "netcoredbg";
-}
 
-impl zed::Extension for NetCoreDbgExtension {
-    fn new() -> Self {
-        Self::default()
+    /// Makes netcoredbg speak the Debug Adapter Protocol on its standard input and output
+    const INTERPRETER_ARG: &str = "--interpreter=vscode";
+
+    /// netcoredbg option writing the DAP traffic and engine events to a file
+    const ENGINE_LOGGING_OPTION: &str = "--engineLogging";
+
+    /// File the engine log is written to by default, next to the extension's log
+    const ENGINE_LOG_FILE: &str = "netcoredbg_engine.log";
+
+    /// Placeholder in `pipeArgs` replaced by the remote netcoredbg command line
+    const DEBUGGER_COMMAND_PLACEHOLDER: &str = "${debuggerCommand}";
+
+    /// How long `waitFor` waits for the process to attach to by default
+    const DEFAULT_WAIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+    /// How often the process list is polled while waiting for the process to attach to
+    const WAIT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+    /// Formats a binary resolution error for display, followed by a hint on how to fix it
+    /// and the versions already installed locally
+    fn binary_error_message(error: &BinaryManagerError, installed: &[InstalledVersion]) -> String {
+        let hint = match error {
+            BinaryManagerError::ReleaseFetch { tag: Some(_), .. } => {
+                "Check that the 'version' setting names one of the available tags."
+            }
+            BinaryManagerError::ReleaseFetch { tag: None, .. } => {
+                "Check your network connection. To work offline, point the 'binary' setting at a local netcoredbg or pin 'version' to an already installed release."
+            }
+            BinaryManagerError::UnsupportedPlatform { .. } => {
+                "Build netcoredbg for this platform and point the 'binary' setting under 'dap.netcoredbg' at it, or set 'force_asset' if a compatible release asset exists."
+            }
+            BinaryManagerError::AssetNotFound { .. } => {
+                "Set 'force_asset' to one of the available assets, or 'download_url' to a compatible archive."
+            }
+            BinaryManagerError::UnsupportedAsset { .. } => {
+                "Set 'force_asset' to a release asset in a supported format, or point the 'binary' setting at a local netcoredbg."
+            }
+            BinaryManagerError::Timeout { .. } => {
+                "GitHub may be unreachable from this network. Point the 'binary' setting under 'dap.netcoredbg' at a local netcoredbg, or set 'download_url' to a reachable mirror."
+            }
+            BinaryManagerError::Download { .. } => {
+                "Check your network connection or proxy. Raising 'retry_attempts' or setting 'download_url' to a mirror may help."
+            }
+            BinaryManagerError::Extraction(_) => {
+                "The archive may be incomplete or laid out differently than expected. Set 'download_url' to another archive, or point the 'binary' setting at a local netcoredbg."
+            }
+            BinaryManagerError::ChecksumMismatch { .. } => {
+                "The download may be corrupted or tampered with. Try again, and double-check the 'checksum' setting if you set one."
+            }
+            BinaryManagerError::VerificationFailed { .. } => {
+                "The build may not match this system. Set 'force_asset' or point the 'binary' setting at a working netcoredbg; 'version_check': false skips this check."
+            }
+            BinaryManagerError::LaunchFailed { .. } => {
+                "Install the libraries netcoredbg reports missing (e.g. libicu), or point the 'binary' setting at a build for this system; 'preflight_check': false skips this check."
+            }
+            BinaryManagerError::InsufficientDiskSpace { .. } => {
+                "Free up disk space, or set 'install_dir' to a location with more room."
+            }
+            BinaryManagerError::UserPathInvalid {
+                scope: PathScope::DebugConfiguration,
+                ..
+            } => "Fix or remove the 'binary' path in the 'netcoredbg' object of the project's debug configuration.",
+            BinaryManagerError::UserPathInvalid {
+                scope: PathScope::ZedSettings,
+                ..
+            } => "Fix or remove the 'binary' path under 'dap.netcoredbg' in your Zed settings.",
+            BinaryManagerError::InvalidSetting { .. } => {
+                "Fix the setting in the 'netcoredbg' object of your debug configuration."
+            }
+            BinaryManagerError::FileSystem(_) => {
+                "Check the permissions of the install directory, or set 'install_dir' to a writable location."
+            }
+        };
+
+        let mut message = format!("{}\n{}", error, hint);
+        message.push_str(&format!(
+            "\nDebug log: {}",
+            Logger::log_file_path().display()
+        ));
+        if !installed.is_empty() {
+            message.push_str(&format!(
+                "\nInstalled locally: {}",
+                installed
+                    .iter()
+                    .map(|installed| installed.tag.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        message
     }
 
-    fn get_dap_binary(
+    /// Formats the last log lines for appending to an error, shortening long lines
+    fn recent_log() -> String {
+        const LINES: usize = 15;
+        const MAX_LINE_CHARS: usize = 200;
+
+        let lines = Logger::recent_lines();
+        let mut recent = String::from("Recent log:");
+        for line in &lines[lines.len().saturating_sub(LINES)..] {
+            recent.push_str("\n  ");
+            match line.char_indices().nth(MAX_LINE_CHARS) {
+                Some((end, _)) => {
+                    recent.push_str(&line[..end]);
+                    recent.push('…');
+                }
+                None => recent.push_str(line),
+            }
+        }
+        recent
+    }
+
+    /// Resolves the netcoredbg binary and launch arguments for one debug session
+    fn dap_binary(
         &mut self,
         adapter_name: String,
         config: DebugTaskDefinition,
@@ -66,8 +364,49 @@ impl zed::Extension for NetCoreDbgExtension {
             return Err(format!("Cannot create binary for adapter: {adapter_name}"));
         }
 
-        let configuration = config.config.to_string();
-        let parsed_config: NetCoreDbgDebugConfig =
+        let mut configuration: Value = serde_json::from_str(&config.config)
+            .map_err(|e| format!("Failed to parse debug configuration: {}", e))?;
+        if let Value::Object(object) = &mut configuration {
+            if object
+                .get("netcoredbg")
+                .is_some_and(|settings| settings.get("github_token").is_some())
+            {
+                return Err("'netcoredbg.github_token' is not supported: debug configurations are often committed, so the token is only read from GITHUB_TOKEN in the environment Zed runs in. Remove it and set GITHUB_TOKEN instead.".to_string());
+            }
+            // Keys of the Zed scenario take precedence over the imported ones
+            if let Some(name) = object.remove("vscodeConfiguration") {
+                let name = name.as_str().ok_or_else(|| {
+                    format!(
+                        "'vscodeConfiguration' must be a configuration name, got {}",
+                        name
+                    )
+                })?;
+                for (key, value) in compat::import_launch_json(&worktree.root_path(), name)? {
+                    object.entry(key).or_insert(value);
+                }
+            }
+            compat::translate_coreclr(object);
+            if let Some(console) = object
+                .get("console")
+                .and_then(Value::as_str)
+                .filter(|console| TERMINAL_CONSOLES.contains(console))
+            {
+                return Err(format!(
+                    "'console': '{}' is not supported: netcoredbg starts the program itself and cannot run it in a terminal, so its output always goes to the debug console, which cannot send input. Remove 'console', or start a program that reads standard input in a Zed terminal and attach to it.",
+                    console
+                ));
+            }
+            let variables = Variables::new(worktree.root_path(), worktree.shell_env());
+            for (key, value) in object.iter_mut() {
+                // Adapter settings expand their paths themselves
+                if key != "netcoredbg" {
+                    variables.substitute(value, key)?;
+                }
+            }
+            validation::validate(object)?;
+        }
+        let configuration = configuration.to_string();
+        let mut parsed_config: NetCoreDbgDebugConfig =
             serde_json::from_str(&configuration).map_err(|e| {
                 format!("Failed to parse debug configuration: {}. Expected NetCoreDbg configuration format.", e)
             })?;
@@ -83,23 +422,1114 @@ impl zed::Extension for NetCoreDbgExtension {
             }
         };
 
-        let binary_path = self
-            .binary_manager
-            .get_binary_path(user_provided_debug_adapter_path)?;
+        let adapter_settings = parsed_config.netcoredbg.clone().unwrap_or_default();
+        let env_level = worktree
+            .shell_env()
+            .into_iter()
+            .find_map(|(key, value)| (key == Logger::LEVEL_ENV_VAR).then_some(value));
+        Logger::configure(&adapter_settings, env_level.as_deref());
+        if let Ok(value) = serde_json::from_str::<Value>(&configuration) {
+            LOG.info(&format!(
+                "Received debug configuration: {}",
+                Logger::redacted(&value)
+            ));
+        }
+
+        if let Some(docker) = &parsed_config.docker {
+            parsed_config.pipe_transport = Some(docker.pipe_transport()?);
+            if parsed_config.source_file_map.is_none() {
+                parsed_config.source_file_map = Some(DockerTarget::default_source_file_map());
+            }
+        }
+
+        let wsl = parsed_config.wsl.clone().or_else(|| {
+            parsed_config
+                .use_wsl
+                .unwrap_or(false)
+                .then(WslTarget::default)
+        });
+        if let Some(wsl) = &wsl {
+            if !matches!(platform::os(), zed::Os::Windows) {
+                return Err(
+                    "'useWSL' and 'wsl' are only supported when Zed runs on Windows".to_string(),
+                );
+            }
+            parsed_config.pipe_transport = Some(wsl.pipe_transport()?);
+            if parsed_config.source_file_map.is_none() {
+                parsed_config.source_file_map = WslTarget::source_file_map(&worktree.root_path());
+            }
+        }
+
+        // Values the extension resolved itself, replacing those in the configuration
+        let mut resolved = serde_json::Map::new();
+        let mut profile = None;
+        let mut launch_project = None;
+        let cwd = match request {
+            StartDebuggingRequestArgumentsRequest::Launch => {
+                let (cwd, run_args) = match &parsed_config.pipe_transport {
+                    Some(_) if parsed_config.launch_mode == LaunchMode::DotnetRun => {
+                        return Err("'launchMode': 'dotnetRun' needs the project on this machine and cannot be combined with a pipe transport; set 'program' to the remote assembly instead".to_string());
+                    }
+                    Some(pipe_transport) => {
+                        if wsl.is_some() {
+                            Self::resolve_wsl_launch(&parsed_config, worktree, &mut resolved)?;
+                        } else {
+                            LOG.info("Launching through pipeTransport; 'program' and 'cwd' are passed on as paths on the remote machine");
+                        }
+                        (Self::pipe_cwd(pipe_transport, worktree)?, Vec::new())
+                    }
+                    None => {
+                        let launch =
+                            self.resolve_launch(&parsed_config, worktree, &mut resolved)?;
+                        profile = launch.profile;
+                        launch_project = launch.project;
+                        (launch.cwd, launch.run_args)
+                    }
+                };
+                let console = parsed_config.console.unwrap_or(Console::InternalConsole);
+                resolved.insert(
+                    "console".to_string(),
+                    serde_json::to_value(console).unwrap_or_default(),
+                );
+                // Sent explicitly so that netcoredbg builds never default to stopping
+                resolved.insert(
+                    "stopAtEntry".to_string(),
+                    Value::Bool(parsed_config.stop_at_entry.unwrap_or(false)),
+                );
+                let profile_args = profile
+                    .as_ref()
+                    .and_then(|profile| profile.command_line_args.clone())
+                    .map(Value::String);
+                let mut program_args = run_args;
+                if let Some(args) = parsed_config.args.as_ref().or(profile_args.as_ref()) {
+                    program_args.extend(args::program_args(args)?);
+                }
+                if !program_args.is_empty() {
+                    resolved.insert("args".to_string(), Value::from(program_args));
+                }
+                cwd
+            }
+            StartDebuggingRequestArgumentsRequest::Attach => {
+                let pid = Self::validate_attach(&parsed_config, &worktree.root_path())?;
+                resolved.insert("processId".to_string(), Value::from(pid));
+                match &parsed_config.pipe_transport {
+                    Some(pipe_transport) => Self::pipe_cwd(pipe_transport, worktree)?,
+                    None => worktree.root_path(),
+                }
+            }
+        };
+
+        resolved.extend(Self::stepping_options(&parsed_config, request));
+
+        if let Some(source_file_map) = &parsed_config.source_file_map {
+            let source_file_map = Self::source_file_map(source_file_map, worktree)?;
+            LOG.info(&format!(
+                "Source file map: {}",
+                Value::Object(source_file_map.clone())
+            ));
+            resolved.insert("sourceFileMap".to_string(), Value::Object(source_file_map));
+        }
+
+        if let Some(symbol_options) = &parsed_config.symbol_options {
+            let symbol_options = Self::symbol_options(symbol_options, &adapter_settings, worktree)?;
+            let symbol_options = serde_json::to_value(&symbol_options)
+                .map_err(|e| format!("Failed to serialize symbolOptions: {}", e))?;
+            LOG.info(&format!("Symbol options: {}", symbol_options));
+            resolved.insert("symbolOptions".to_string(), symbol_options);
+        }
+
+        let mut environment = Environment::new(worktree.shell_env());
+        let secrets_id = match (parsed_config.use_user_secrets, &launch_project) {
+            (Some(false), _) => None,
+            (use_user_secrets, Some(project)) => {
+                let id = Project::user_secrets_id(project);
+                if id.is_none() && use_user_secrets == Some(true) {
+                    LOG.warn(&format!(
+                        "'useUserSecrets' is set, but '{}' has no UserSecretsId; run `dotnet user-secrets init` in its directory",
+                        project.display()
+                    ));
+                }
+                id
+            }
+            (Some(true), None) => {
+                LOG.warn("'useUserSecrets' is set, but the program's project was not found");
+                None
+            }
+            (None, None) => None,
+        };
+        let secrets = secrets_id
+            .map(|id| user_secrets::env_vars(&id, &worktree.shell_env().into_iter().collect()))
+            .unwrap_or_default();
+        let secrets_loaded = !secrets.is_empty();
+        Logger::redact_keys(secrets.iter().map(|(key, _)| key.clone()));
+        environment.apply(secrets);
+        if let Some(profile) = &profile {
+            environment.apply(profile.env_vars()?);
+        }
+        if let Some(env_file) = &parsed_config.env_file {
+            let path = Self::config_path(env_file, worktree)?;
+            environment.apply(Environment::env_file_vars(&path)?);
+            LOG.info(&format!("Loaded environment from {}", path.display()));
+        }
+        if let Some(name) = &parsed_config.environment {
+            environment.apply(Environment::hosting_vars(name));
+        }
+        let suppress_jit_optimizations = parsed_config.suppress_jit_optimizations == Some(true)
+            && request == StartDebuggingRequestArgumentsRequest::Launch;
+        if suppress_jit_optimizations {
+            environment.apply(Environment::unoptimized_jit_vars());
+        }
+        environment.apply(Environment::config_vars(&parsed_config.env)?);
+        let default_environment = launch_project.as_deref().is_some_and(Project::is_web)
+            && environment.apply_default_hosting();
+        if default_environment {
+            LOG.debug(&format!(
+                "No hosting environment is set for the ASP.NET Core project; defaulting to '{}'",
+                DEFAULT_HOSTING_ENVIRONMENT
+            ));
+        }
+        if request == StartDebuggingRequestArgumentsRequest::Launch
+            && parsed_config.pipe_transport.is_none()
+            && !parsed_config.skip_port_check.unwrap_or(false)
+        {
+            let is_web_project = launch_project.as_deref().is_some_and(Project::is_web);
+            Self::check_ports(&environment, is_web_project)?;
+        }
+        let envs = environment.into_vars();
+        if !parsed_config.env.is_empty()
+            || parsed_config.env_file.is_some()
+            || secrets_loaded
+            || parsed_config.environment.is_some()
+            || suppress_jit_optimizations
+            || default_environment
+        {
+            let env = envs
+                .iter()
+                .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+                .collect();
+            resolved.insert("env".to_string(), Value::Object(env));
+        }
+
+        // Adapter settings only concern the extension and may hold a GitHub token, and the pipe
+        // transport, container, WSL target and adapter arguments only concern how netcoredbg
+        // is spawned, so they are not forwarded to it
+        let configuration = match serde_json::from_str::<Value>(&configuration) {
+            Ok(Value::Object(mut object)) => {
+                object.remove("netcoredbg");
+                object.remove("pipeTransport");
+                object.remove("docker");
+                object.remove("useWSL");
+                object.remove("wsl");
+                object.remove("adapterArgs");
+                object.remove("engineLogging");
+                object.extend(resolved);
+                Value::Object(object).to_string()
+            }
+            _ => configuration,
+        };
 
-        Ok(DebugAdapterBinary {
-            command: Some(binary_path),
-            arguments: vec!["--interpreter=vscode".to_string()],
-            envs: parsed_config.env.into_iter().collect(),
-            cwd: Some(parsed_config.cwd.unwrap_or_else(|| worktree.root_path())),
+        let (command, arguments) = match &parsed_config.pipe_transport {
+            Some(pipe_transport) => {
+                Self::pipe_command(pipe_transport, &Self::adapter_args(&parsed_config, true)?)?
+            }
+            None => {
+                let binary_path = self
+                    .binary_manager
+                    .get_binary_path(
+                        user_provided_debug_adapter_path,
+                        &adapter_settings,
+                        worktree,
+                    )
+                    .and_then(|binary_path| {
+                        self.binary_manager
+                            .preflight(&binary_path, &adapter_settings)
+                            .map(|()| binary_path)
+                    })
+                    .map_err(|e| {
+                        let installed = self
+                            .binary_manager
+                            .list_installed_versions(&adapter_settings, worktree);
+                        let message = Self::binary_error_message(&e, &installed);
+                        LOG.error(&message);
+                        message
+                    })?;
+                if let Some(version) = self.binary_manager.installed_version() {
+                    LOG.info(&format!("Using netcoredbg {} at {}", version, binary_path));
+                }
+                let mut arguments = Self::adapter_args(&parsed_config, false)?;
+                if let Some(first_launch_log) = BinaryManager::first_launch_log(&binary_path) {
+                    let engine_log_prefix = format!("{}=", Self::ENGINE_LOGGING_OPTION);
+                    let engine_log = match arguments
+                        .iter()
+                        .find_map(|arg| arg.strip_prefix(&engine_log_prefix))
+                    {
+                        Some(engine_log) => engine_log.to_string(),
+                        None => {
+                            let engine_log = first_launch_log.to_string_lossy().into_owned();
+                            arguments.push(format!("{}{}", engine_log_prefix, engine_log));
+                            engine_log
+                        }
+                    };
+                    BinaryManager::record_first_launch(&binary_path, &engine_log);
+                }
+                (binary_path, arguments)
+            }
+        };
+        let binary = DebugAdapterBinary {
+            command: Some(command),
+            arguments,
+            envs: envs.into_iter().collect(),
+            cwd: Some(cwd),
             connection: None,
             request_args: StartDebuggingRequestArguments {
                 configuration,
                 request,
             },
+        };
+        Self::log_launch(&binary);
+        Ok(binary)
+    }
+
+    /// Stepping options sent to netcoredbg: `justMyCode` defaults to true, unless
+    /// `enableSourceLink` asks for stepping into library code
+    fn stepping_options(
+        config: &NetCoreDbgDebugConfig,
+        request: StartDebuggingRequestArgumentsRequest,
+    ) -> serde_json::Map<String, Value> {
+        let source_link = config.enable_source_link.unwrap_or(false);
+        if source_link {
+            LOG.warn("netcoredbg cannot download sources through Source Link; library frames only show sources mapped to a local checkout with 'sourceFileMap'");
+            match config.just_my_code {
+                Some(true) => LOG.warn("'justMyCode' is true, so stepping does not enter library code despite 'enableSourceLink'"),
+                Some(false) => {}
+                None => LOG.info("'enableSourceLink' is set; disabling 'justMyCode' so that stepping enters library code"),
+            }
+        }
+        if config.suppress_jit_optimizations == Some(true) {
+            match request {
+                StartDebuggingRequestArgumentsRequest::Attach => LOG.warn("'suppressJITOptimizations' is ignored: the running process has already compiled its code, so only launch configurations can apply it"),
+                StartDebuggingRequestArgumentsRequest::Launch if config.just_my_code != Some(false) => LOG.info("'suppressJITOptimizations' is set while 'justMyCode' is on, so only code with symbols is stepped through; set 'justMyCode' to false to see the locals of library code as well"),
+                StartDebuggingRequestArgumentsRequest::Launch => {}
+            }
+        }
+        // Sent explicitly for launch and attach so that the documented default applies to both
+        let just_my_code = config.just_my_code.unwrap_or(!source_link);
+        [("justMyCode".to_string(), Value::Bool(just_my_code))]
+            .into_iter()
+            .collect()
+    }
+
+    /// Resolves the program, launch profile and working directory of a local launch,
+    /// recording the program and working directory in `resolved`
+    fn resolve_launch(
+        &mut self,
+        config: &NetCoreDbgDebugConfig,
+        worktree: &Worktree,
+        resolved: &mut serde_json::Map<String, Value>,
+    ) -> Result<LocalLaunch, String> {
+        let (program, run_args) = match self.prepare_program(config, worktree)? {
+            Some((program, run_args)) => (Some(program), run_args),
+            None => (None, Vec::new()),
+        };
+        let program_path = match (&program, config.program.as_deref()) {
+            (Some(program), _) => Some(std::path::PathBuf::from(program)),
+            (None, Some(program)) if program != AUTO_PROGRAM => {
+                Some(Self::config_path(program, worktree)?)
+            }
+            _ => None,
+        };
+        if let Some(program_path) = &program_path {
+            if !config.skip_target_validation.unwrap_or(false) {
+                Self::check_target(program_path)?;
+            }
+            self.dotnet(worktree)?.check_runtimes(program_path)?;
+        }
+        let project_dir = program_path.as_deref().and_then(Project::dir_containing);
+        let profile = Self::launch_profile(config, project_dir.as_deref())?;
+        let cwd = Self::launch_cwd(config, project_dir.as_deref(), profile.as_ref(), worktree)?;
+        if let Some(program) = program {
+            resolved.insert("program".to_string(), Value::String(program));
+        }
+        resolved.insert("cwd".to_string(), Value::String(cwd.clone()));
+        Ok(LocalLaunch {
+            profile,
+            cwd,
+            run_args,
+            project: project_dir.and_then(|dir| Project::find(&dir, false, None).ok()),
         })
     }
 
+    /// Checks that the ports the program is configured to listen on through `environment` are
+    /// free, naming the process holding them otherwise. ASP.NET Core projects without any
+    /// configured URL listen on Kestrel's default, which may also be set in code or
+    /// `appsettings.json`, so a conflict there is only a warning.
+    fn check_ports(environment: &Environment, is_web_project: bool) -> Result<(), String> {
+        let configured_urls = ports::URL_VARS
+            .iter()
+            .find_map(|key| Some((*key, environment.get(key)?)));
+        let configured_ports: Vec<(&str, u16)> = ports::PORT_VARS
+            .iter()
+            .filter_map(|key| Some((*key, environment.get(key)?)))
+            .flat_map(|(key, ports)| {
+                ports::listed_ports(ports)
+                    .into_iter()
+                    .map(move |port| (key, port))
+            })
+            .collect();
+        let (ports, is_default): (Vec<(&str, u16)>, bool) = match configured_urls {
+            Some((key, urls)) => (
+                ports::url_ports(urls)
+                    .into_iter()
+                    .map(|port| (key, port))
+                    .collect(),
+                false,
+            ),
+            None if !configured_ports.is_empty() => (configured_ports, false),
+            None if is_web_project => (
+                ports::url_ports(ports::DEFAULT_URL)
+                    .into_iter()
+                    .map(|port| ("the default URL", port))
+                    .collect(),
+                true,
+            ),
+            None => return Ok(()),
+        };
+
+        let mut conflicts = Vec::new();
+        for (source, port) in ports {
+            match ports::listener(port) {
+                Some(Some(listener)) => conflicts.push(format!(
+                    "port {} (from {}) is in use by {}process {}",
+                    port,
+                    source,
+                    listener
+                        .name
+                        .map(|name| format!("{} ", name))
+                        .unwrap_or_default(),
+                    listener.pid
+                )),
+                Some(None) => {}
+                None => {
+                    LOG.debug("Cannot tell which ports are in use; skipping the port check");
+                    return Ok(());
+                }
+            }
+        }
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+        let message = format!(
+            "The program cannot listen on its ports: {}. Stop that process, pick another port, e.g. with \"env\": {{\"ASPNETCORE_URLS\": \"http://localhost:5080\"}}, or set 'skipPortCheck' to true if the program binds its ports otherwise.",
+            conflicts.join("; ")
+        );
+        if is_default {
+            LOG.warn(&message);
+            return Ok(());
+        }
+        Err(message)
+    }
+
+    /// Rejects programs netcoredbg cannot debug, judging from their headers: .NET Framework
+    /// assemblies, native code such as NativeAOT executables, and executables published as a
+    /// single file, whose assemblies are loaded from the bundle in memory. The bundle error
+    /// suggests the regular build output of the project the executable was published from.
+    fn check_target(program: &std::path::Path) -> Result<(), String> {
+        const SKIP_HINT: &str = "Set 'skipTargetValidation' to true if this is wrong.";
+        match assembly::classify(program) {
+            Some(TargetKind::NetFramework) => {
+                return Err(format!(
+                    "'{}' targets the .NET Framework, which netcoredbg cannot debug; it supports .NET (Core) only. Retarget the project to .NET, e.g. net8.0. {}",
+                    program.display(),
+                    SKIP_HINT
+                ))
+            }
+            Some(TargetKind::Native) => {
+                return Err(format!(
+                    "'{}' contains no managed code, e.g. because it was published with NativeAOT, so netcoredbg cannot debug it. Launch the project's regular build output instead, or use a native debugger. {}",
+                    program.display(),
+                    SKIP_HINT
+                ))
+            }
+            Some(TargetKind::CoreClr) | None => {}
+        }
+
+        if !assembly::is_single_file_bundle(program) {
+            return Ok(());
+        }
+        let suggestion = Project::dir_containing(program)
+            .and_then(|dir| Project::find(&dir, false, None).ok())
+            .and_then(|path| Project::load(&path).ok())
+            .and_then(|project| project.output_assembly(None, None).ok())
+            .map(|assembly| format!("'{}'", assembly.display()))
+            .unwrap_or_else(|| "bin/<configuration>/<framework>".to_string());
+        Err(format!(
+            "'{}' was published as a single file, which bundles its assemblies and symbols into the executable where netcoredbg cannot debug them. Launch the regular build output instead, e.g. {}, or publish without PublishSingleFile.",
+            program.display(),
+            suggestion
+        ))
+    }
+
+    /// The command spawning the remote netcoredbg through `pipeProgram`. The netcoredbg command
+    /// line replaces `${debuggerCommand}` in `pipeArgs`, or is appended to them.
+    fn pipe_command(
+        pipe_transport: &PipeTransport,
+        adapter_args: &[String],
+    ) -> Result<(String, Vec<String>), String> {
+        let pipe_program = pipe_transport.pipe_program.clone().ok_or_else(|| {
+            "'pipeTransport' requires 'pipeProgram', the program connecting to the remote machine (e.g. \"ssh\")".to_string()
+        })?;
+        let debugger_path = pipe_transport.debugger_path.as_deref().ok_or_else(|| {
+            "'pipeTransport' requires 'debuggerPath', the path of netcoredbg on the remote machine (e.g. \"/usr/local/bin/netcoredbg\")".to_string()
+        })?;
+
+        let quote = |arg: &str| {
+            if pipe_transport.quote_args.unwrap_or(true) && arg.contains(char::is_whitespace) {
+                format!("\"{}\"", arg.replace('"', "\\\""))
+            } else {
+                arg.to_string()
+            }
+        };
+        let debugger_command: Vec<String> = std::iter::once(debugger_path)
+            .chain(adapter_args.iter().map(String::as_str))
+            .map(quote)
+            .collect();
+
+        let mut arguments =
+            Vec::with_capacity(pipe_transport.pipe_args.len() + debugger_command.len());
+        let mut replaced = false;
+        for arg in &pipe_transport.pipe_args {
+            if arg.contains(Self::DEBUGGER_COMMAND_PLACEHOLDER) {
+                arguments.push(arg.replace(
+                    Self::DEBUGGER_COMMAND_PLACEHOLDER,
+                    &debugger_command.join(" "),
+                ));
+                replaced = true;
+            } else {
+                arguments.push(arg.clone());
+            }
+        }
+        if !replaced {
+            arguments.extend(debugger_command);
+        }
+        Ok((pipe_program, arguments))
+    }
+
+    /// The command line options of netcoredbg: the DAP interpreter, `adapterArgs` and the
+    /// engine log requested with `engineLogging`. Repeated options are passed once, and
+    /// options set in `adapterArgs` win over `engineLogging`. A `remote` netcoredbg cannot
+    /// write to the extension's log directory, so it needs an explicit engine log path.
+    fn adapter_args(config: &NetCoreDbgDebugConfig, remote: bool) -> Result<Vec<String>, String> {
+        let option_name = |arg: &str| arg.split('=').next().unwrap_or(arg).to_string();
+        let mut args = vec![Self::INTERPRETER_ARG.to_string()];
+        for arg in &config.adapter_args {
+            if option_name(arg) == option_name(Self::INTERPRETER_ARG) {
+                LOG.warn(&format!(
+                    "'adapterArgs' entry '{}' is ignored; the extension talks to netcoredbg through {}",
+                    arg,
+                    Self::INTERPRETER_ARG
+                ));
+            } else if args.contains(arg) {
+                LOG.debug(&format!("Skipping repeated adapter argument '{}'", arg));
+            } else {
+                args.push(arg.clone());
+            }
+        }
+
+        let engine_log = match &config.engine_logging {
+            None | Some(EngineLogging::Enabled(false)) => None,
+            Some(EngineLogging::Path(path)) => Some(path.clone()),
+            Some(EngineLogging::Enabled(true)) if remote => {
+                return Err("'engineLogging' must be a path on the remote machine when netcoredbg runs through a pipe transport".to_string());
+            }
+            Some(EngineLogging::Enabled(true)) => Some(
+                Logger::log_file_path()
+                    .with_file_name(Self::ENGINE_LOG_FILE)
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+        };
+        if let Some(engine_log) = engine_log {
+            if args
+                .iter()
+                .any(|arg| option_name(arg) == Self::ENGINE_LOGGING_OPTION)
+            {
+                LOG.warn(
+                    "'engineLogging' is ignored, as 'adapterArgs' already sets --engineLogging",
+                );
+            } else {
+                LOG.info(&format!("netcoredbg engine log: {}", engine_log));
+                args.push(format!("{}={}", Self::ENGINE_LOGGING_OPTION, engine_log));
+            }
+        }
+        Ok(args)
+    }
+
+    /// Translates `program` and `cwd` (default: the worktree root) to the paths the WSL
+    /// distribution sees them at. Linux paths are passed on unchanged.
+    fn resolve_wsl_launch(
+        config: &NetCoreDbgDebugConfig,
+        worktree: &Worktree,
+        resolved: &mut serde_json::Map<String, Value>,
+    ) -> Result<(), String> {
+        let program = config.program.as_deref().ok_or_else(|| {
+            "Launching in WSL requires 'program', the assembly or executable to run".to_string()
+        })?;
+        let to_wsl = |path: &str| -> Result<String, String> {
+            let path = Self::config_path(path, worktree)?
+                .to_string_lossy()
+                .into_owned();
+            Ok(paths::to_wsl_path(&path).unwrap_or(path))
+        };
+        let program = to_wsl(program)?;
+        let cwd = to_wsl(config.cwd.as_deref().unwrap_or("${workspaceFolder}"))?;
+        LOG.info(&format!("Launching {} in WSL, in {}", program, cwd));
+        resolved.insert("program".to_string(), Value::String(program));
+        resolved.insert("cwd".to_string(), Value::String(cwd));
+        Ok(())
+    }
+
+    /// The local directory `pipeProgram` runs in: `pipeCwd`, or else the worktree root
+    fn pipe_cwd(pipe_transport: &PipeTransport, worktree: &Worktree) -> Result<String, String> {
+        match &pipe_transport.pipe_cwd {
+            Some(pipe_cwd) => Ok(Self::config_path(pipe_cwd, worktree)?
+                .to_string_lossy()
+                .into_owned()),
+            None => Ok(worktree.root_path()),
+        }
+    }
+
+    /// The `dotnet` host, located on first use. Only a successful lookup is kept, so that
+    /// installing .NET does not require restarting Zed.
+    fn dotnet(&mut self, worktree: &Worktree) -> Result<&Dotnet, String> {
+        match &mut self.dotnet {
+            Some(dotnet) => Ok(dotnet),
+            slot @ None => Ok(slot.insert(Dotnet::locate(worktree)?)),
+        }
+    }
+
+    /// Resolves a path from the debug configuration. `~`, environment variables and
+    /// `${workspaceFolder}` are expanded, and relative paths resolve against the worktree root.
+    fn config_path(path: &str, worktree: &Worktree) -> Result<std::path::PathBuf, String> {
+        let root = worktree.root_path();
+        let mut variables: HashMap<String, String> = worktree.shell_env().into_iter().collect();
+        variables.insert("workspaceFolder".to_string(), root.clone());
+        variables.insert("ZED_WORKTREE_ROOT".to_string(), root.clone());
+        let expanded = paths::expand_path(path, &variables)?;
+        if paths::is_absolute_path(&expanded) {
+            return Ok(std::path::PathBuf::from(expanded));
+        }
+        Ok(std::path::Path::new(&root).join(expanded))
+    }
+
+    /// Expands the local directories of `sourceFileMap`. The keys are build-time paths on
+    /// another machine and are kept verbatim.
+    fn source_file_map(
+        source_file_map: &serde_json::Map<String, Value>,
+        worktree: &Worktree,
+    ) -> Result<serde_json::Map<String, Value>, String> {
+        source_file_map
+            .iter()
+            .map(|(build_path, local_path)| {
+                let local_path = local_path.as_str().ok_or_else(|| {
+                    format!(
+                        "'sourceFileMap' entry '{}' must map to a path string, got {}",
+                        build_path, local_path
+                    )
+                })?;
+                let local_path = Self::config_path(local_path, worktree)?;
+                Ok((
+                    build_path.clone(),
+                    Value::String(local_path.to_string_lossy().into_owned()),
+                ))
+            })
+            .collect()
+    }
+
+    /// Expands the paths of `symbolOptions`, warning about search directories that do not exist.
+    /// Downloaded symbols are cached in the install root unless `cachePath` is set.
+    fn symbol_options(
+        options: &SymbolOptions,
+        settings: &AdapterSettings,
+        worktree: &Worktree,
+    ) -> Result<SymbolOptions, String> {
+        let is_url = |path: &str| path.starts_with("http://") || path.starts_with("https://");
+
+        let mut search_paths = Vec::with_capacity(options.search_paths.len());
+        for path in &options.search_paths {
+            if is_url(path) {
+                search_paths.push(path.clone());
+                continue;
+            }
+            let expanded = Self::config_path(path, worktree)?;
+            if !expanded.is_dir() {
+                LOG.warn(&format!(
+                    "Symbol search path '{}' does not exist",
+                    expanded.display()
+                ));
+            }
+            search_paths.push(expanded.to_string_lossy().into_owned());
+        }
+
+        let uses_symbol_server = options.search_microsoft_symbol_server == Some(true)
+            || search_paths.iter().any(|path| is_url(path));
+        let cache_path = match &options.cache_path {
+            Some(cache_path) => Some(Self::config_path(cache_path, worktree)?),
+            None if uses_symbol_server => Some(
+                BinaryManager::symbol_cache_dir(settings, worktree).map_err(|e| e.to_string())?,
+            ),
+            None => None,
+        };
+
+        Ok(SymbolOptions {
+            search_paths,
+            search_microsoft_symbol_server: options.search_microsoft_symbol_server,
+            cache_path: cache_path.map(|path| path.to_string_lossy().into_owned()),
+        })
+    }
+
+    /// The launch profile named by `launchProfile`, or else the first profile running the
+    /// project in `project_dir`, from the project's `Properties/launchSettings.json`
+    fn launch_profile(
+        config: &NetCoreDbgDebugConfig,
+        project_dir: Option<&std::path::Path>,
+    ) -> Result<Option<LaunchProfile>, String> {
+        let Some(project_dir) = project_dir else {
+            return match &config.launch_profile {
+                Some(name) => Err(format!(
+                    "Cannot apply launch profile '{}': no project found above the program",
+                    name
+                )),
+                None => Ok(None),
+            };
+        };
+        let profile = LaunchProfile::load(project_dir, config.launch_profile.as_deref())?;
+        if let Some(profile) = &profile {
+            LOG.info(&format!(
+                "Applying launch profile '{}' of {}",
+                profile.name,
+                project_dir.display()
+            ));
+        }
+        Ok(profile)
+    }
+
+    /// The working directory of the launched program: `cwd` if set, otherwise the launch
+    /// profile's `workingDirectory`, the directory of the project that builds the program, or
+    /// else the worktree root
+    fn launch_cwd(
+        config: &NetCoreDbgDebugConfig,
+        project_dir: Option<&std::path::Path>,
+        profile: Option<&LaunchProfile>,
+        worktree: &Worktree,
+    ) -> Result<String, String> {
+        let profile_dir = profile.and_then(|profile| profile.working_directory.as_deref());
+        let cwd = match (&config.cwd, profile_dir, project_dir) {
+            (Some(cwd), _, _) => Self::config_path(cwd, worktree)?,
+            (None, Some(working_directory), Some(project_dir)) => {
+                let variables: HashMap<String, String> = worktree.shell_env().into_iter().collect();
+                let working_directory = working_directory
+                    .replace("$(ProjectDir)", &project_dir.to_string_lossy())
+                    .replace("$(MSBuildProjectDirectory)", &project_dir.to_string_lossy());
+                project_dir.join(paths::expand_path(&working_directory, &variables)?)
+            }
+            (None, _, Some(project_dir)) => project_dir.to_path_buf(),
+            (None, _, None) => std::path::PathBuf::from(worktree.root_path()),
+        };
+        if !cwd.is_dir() {
+            return Err(format!(
+                "Working directory '{}' does not exist. Check the 'cwd' of this debug configuration.",
+                cwd.display()
+            ));
+        }
+        LOG.info(&format!("Working directory: {}", cwd.display()));
+        Ok(cwd.to_string_lossy().into_owned())
+    }
+
+    /// Checks an attach configuration and returns the ID of the process to attach to, which
+    /// must be running
+    fn validate_attach(config: &NetCoreDbgDebugConfig, worktree_root: &str) -> Result<u32, String> {
+        let pick_process = || {
+            if config.pipe_transport.is_some() {
+                Err("Processes on the remote machine cannot be looked up; set 'processId' to the ID of the process to attach to".to_string())
+            } else {
+                Self::pick_process(worktree_root)
+            }
+        };
+        if let Some(name) = &config.process_name {
+            if config.process_id.is_some() {
+                return Err(
+                    "'processId' and 'processName' cannot be combined; remove one of them"
+                        .to_string(),
+                );
+            }
+            if config.pipe_transport.is_some() {
+                return Err("Processes on the remote machine cannot be looked up by name; set 'processId' to the ID of the process to attach to".to_string());
+            }
+            let timeout = config
+                .timeout
+                .map_or(Self::DEFAULT_WAIT_TIMEOUT, std::time::Duration::from_secs);
+            return Self::find_named_process(
+                name,
+                config.wait_for.unwrap_or(false),
+                timeout,
+                worktree_root,
+            );
+        }
+        if config.wait_for.is_some() || config.timeout.is_some() {
+            LOG.warn("'waitFor' and 'timeout' are ignored without 'processName'");
+        }
+        let pid = match &config.process_id {
+            None => return pick_process(),
+            Some(ProcessId::String(pid)) if pid == PICK_PROCESS => return pick_process(),
+            Some(ProcessId::Int(pid)) => u32::try_from(*pid).ok().filter(|pid| *pid > 0),
+            Some(ProcessId::String(pid)) => pid.trim().parse::<u32>().ok().filter(|pid| *pid > 0),
+        };
+        let pid = pid.ok_or_else(|| {
+            format!(
+                "'processId' must be a positive process ID, got {}",
+                serde_json::to_string(&config.process_id).unwrap_or_default()
+            )
+        })?;
+
+        if config.pipe_transport.is_some() {
+            return Ok(pid);
+        }
+        match processes::process_exists(pid) {
+            Some(true) => Ok(pid),
+            Some(false) => Err(format!(
+                "No process with ID {} is running. Check the 'processId' of this attach configuration.",
+                pid
+            )),
+            None => {
+                LOG.warn(&format!(
+                    "Could not check whether process {} is running",
+                    pid
+                ));
+                Ok(pid)
+            }
+        }
+    }
+
+    /// Looks up the process named `name` to attach to. If several are running, the newest one
+    /// started from the worktree is preferred, and else the newest one. With `wait_for`, the
+    /// processes already running are ignored and the process list is polled until a new one
+    /// starts, for at most `timeout`.
+    fn find_named_process(
+        name: &str,
+        wait_for: bool,
+        timeout: std::time::Duration,
+        worktree_root: &str,
+    ) -> Result<u32, String> {
+        let started = std::time::Instant::now();
+        let running = processes::named(name)?;
+        let candidates = if wait_for {
+            let ignored: Vec<u32> = running.iter().map(|process| process.pid).collect();
+            if !ignored.is_empty() {
+                LOG.info(&format!(
+                    "Ignoring the running processes named '{}': {}",
+                    name,
+                    Self::describe_pids(&running)
+                ));
+            }
+            LOG.info(&format!(
+                "Waiting up to {} s for a process named '{}' to start",
+                timeout.as_secs(),
+                name
+            ));
+            loop {
+                let started_since: Vec<processes::DotnetProcess> = processes::named(name)?
+                    .into_iter()
+                    .filter(|process| !ignored.contains(&process.pid))
+                    .collect();
+                if !started_since.is_empty() {
+                    break started_since;
+                }
+                if started.elapsed() >= timeout {
+                    let hint = if ignored.is_empty() {
+                        ". Check that it is started under this name, or raise 'timeout'."
+                            .to_string()
+                    } else {
+                        format!(
+                            "; the processes already running ({}) were ignored. Remove 'waitFor' to attach to one of them.",
+                            Self::describe_pids(&running)
+                        )
+                    };
+                    return Err(format!(
+                        "Timed out after {} s waiting for a process named '{}' to start{}",
+                        timeout.as_secs(),
+                        name,
+                        hint
+                    ));
+                }
+                std::thread::sleep(Self::WAIT_POLL_INTERVAL);
+            }
+        } else {
+            running
+        };
+
+        let Some(newest) = candidates.first() else {
+            return Err(format!(
+                "No process named '{}' is running. Start it first, or set 'waitFor' to true to wait for it to start.",
+                name
+            ));
+        };
+        let chosen = if candidates.len() > 1 {
+            let from_worktree = processes::running_from(&candidates, worktree_root);
+            let (chosen, which) = match from_worktree.first() {
+                Some(chosen) => (*chosen, "the newest started from the worktree"),
+                None => (newest, "the newest"),
+            };
+            let others: Vec<processes::DotnetProcess> = candidates
+                .iter()
+                .filter(|process| process.pid != chosen.pid)
+                .cloned()
+                .collect();
+            LOG.warn(&format!(
+                "Several processes are named '{}'; attaching to {}. Also running: {}",
+                name,
+                which,
+                Self::describe_pids(&others)
+            ));
+            chosen
+        } else {
+            newest
+        };
+        LOG.info(&format!(
+            "Attaching to process {} ({})",
+            chosen.pid, chosen.command_line
+        ));
+        Ok(chosen.pid)
+    }
+
+    fn describe_pids(processes: &[processes::DotnetProcess]) -> String {
+        processes
+            .iter()
+            .map(|process| format!("{} ({})", process.pid, process.command_line))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Looks up the process to attach to when no `processId` is given. Zed offers extensions
+    /// no way to show a picker, so a single running .NET process, or else the only one started
+    /// from the worktree, is attached to directly, and several are listed in the error for the
+    /// user to choose from.
+    fn pick_process(worktree_root: &str) -> Result<u32, String> {
+        let processes = processes::list_dotnet_processes()?;
+        // A test host started with VSTEST_HOST_DEBUG, or a Functions worker started with
+        // --dotnet-isolated-debug, is waiting for exactly this
+        for kind in [WaitingProcess::TestHost, WaitingProcess::FunctionsWorker] {
+            let candidates: Vec<&processes::DotnetProcess> = processes
+                .iter()
+                .filter(|process| WaitingProcess::of(process) == Some(kind))
+                .collect();
+            if let [process] = candidates.as_slice() {
+                LOG.info(&format!(
+                    "Attaching to the {} {} ({})",
+                    kind.describe(),
+                    process.pid,
+                    process.command_line
+                ));
+                return Ok(process.pid);
+            }
+        }
+        match processes.as_slice() {
+            [process] => {
+                LOG.info(&format!(
+                    "Attaching to the only running .NET process {} ({})",
+                    process.pid, process.command_line
+                ));
+                Ok(process.pid)
+            }
+            [] => Err("No running .NET process found to attach to. Start the application first, or set 'processId'.".to_string()),
+            processes => {
+                let from_worktree = processes::running_from(processes, worktree_root);
+                if let [process] = from_worktree.as_slice() {
+                    LOG.info(&format!(
+                        "Attaching to {} ({}), the only one of {} running .NET processes started from the worktree",
+                        process.pid,
+                        process.command_line,
+                        processes.len()
+                    ));
+                    return Ok(process.pid);
+                }
+                // Those started from the worktree are the likelier choices, so they come first
+                let listed = from_worktree.iter().copied().chain(
+                    processes
+                        .iter()
+                        .filter(|process| !from_worktree.iter().any(|local| local.pid == process.pid)),
+                );
+                Err(format!(
+                    "Several .NET processes are running. Set 'processId' to one of them, or pick one in the Attach tab of Zed's new debug session dialog:\n{}",
+                    listed
+                        .map(|process| format!("  {}  {}", process.pid, process.command_line))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                ))
+            }
+        }
+    }
+
+    /// Builds the project if `buildProject` asks for it, and locates the assembly it builds
+    /// when `program` is not set. The project is the configured one or the nearest one below
+    /// `cwd`, or else the worktree root. Returns the program to launch if it was located, with
+    /// the arguments of the `dotnet run` command when `launchMode` asks for it.
+    fn prepare_program(
+        &mut self,
+        config: &NetCoreDbgDebugConfig,
+        worktree: &Worktree,
+    ) -> Result<Option<(String, Vec<String>)>, String> {
+        let explicit_program = config
+            .program
+            .as_deref()
+            .filter(|program| *program != AUTO_PROGRAM);
+        if explicit_program.is_some() && config.launch_mode == LaunchMode::DotnetRun {
+            return Err("'launchMode': 'dotnetRun' launches what `dotnet run` starts for the project, so 'program' must not be set. Remove 'program', or set 'launchMode' to 'dll' to launch it directly.".to_string());
+        }
+        if explicit_program.is_some() && config.build_project == BuildMode::Never {
+            return Ok(None);
+        }
+
+        let root = std::path::PathBuf::from(worktree.root_path());
+        let project = match (&config.project, &config.cwd) {
+            (Some(project), _) => Self::config_path(project, worktree)?,
+            (None, Some(cwd)) => Self::config_path(cwd, worktree)?,
+            (None, None) => root.clone(),
+        };
+        let worktree_root = worktree.root_path();
+        let found = Project::find(
+            &project,
+            config.project.is_some(),
+            Project::remembered(&worktree_root).as_deref(),
+        )?;
+        if config.project.is_some() {
+            Project::remember(&worktree_root, &found);
+        }
+        if explicit_program.is_none() && Project::is_functions(&found) {
+            return Err(format!(
+                "'{}' is an Azure Functions app, whose assembly only runs inside the Functions host, which the extension cannot start and follow. Run `func start --dotnet-isolated-debug` in a Zed task in the project directory, then start an attach configuration without 'processId' once the host prints the worker's process ID; the waiting worker is picked automatically.",
+                found.display()
+            ));
+        }
+        let project = Project::load(&found)?;
+        let program = match explicit_program {
+            Some(program) => Self::config_path(program, worktree)?,
+            None => project.output_assembly(
+                config.configuration.as_deref(),
+                config.target_framework.as_deref(),
+            )?,
+        };
+
+        let needs_build = match config.build_project {
+            BuildMode::Never => false,
+            BuildMode::Always => true,
+            BuildMode::Auto => !project.is_up_to_date(&program),
+        };
+        if needs_build {
+            project.build(
+                self.dotnet(worktree)?,
+                project.configuration(config.configuration.as_deref()),
+                &worktree.shell_env(),
+            )?;
+        } else if config.build_project == BuildMode::Auto {
+            LOG.info(&format!(
+                "Skipping build, {} is up to date",
+                program.display()
+            ));
+        }
+
+        if explicit_program.is_some() {
+            return Ok(None);
+        }
+        let apphost = Project::apphost(&program);
+        if !program.is_file() && apphost.is_none() {
+            let configuration = project.configuration(config.configuration.as_deref());
+            let built = project.built_frameworks(configuration);
+            if !built.is_empty() {
+                return Err(format!(
+                    "'{}' does not exist. The project has builds for: {}. Set 'targetFramework' to one of them, or build the project for the missing framework.",
+                    program.display(),
+                    built.join(", ")
+                ));
+            }
+            return Err(format!(
+                "'{}' does not exist yet. Build the project first, e.g. with `dotnet build {}`, or set 'buildProject' to build it before debugging.",
+                program.display(),
+                project.path.display()
+            ));
+        }
+        if config.launch_mode == LaunchMode::DotnetRun {
+            let (run_program, run_args) = project.run_command(
+                self.dotnet(worktree)?,
+                project.configuration(config.configuration.as_deref()),
+                &program,
+                &worktree.shell_env(),
+            )?;
+            LOG.info(&format!(
+                "Resolved run command {} {:?} from {}",
+                run_program,
+                run_args,
+                project.path.display()
+            ));
+            return Ok(Some((run_program, run_args)));
+        }
+        let program = Project::launch_target(&program, config.use_apphost)?;
+        LOG.info(&format!(
+            "Resolved program {} from {}",
+            program.display(),
+            project.path.display()
+        ));
+        Ok(Some((program.to_string_lossy().into_owned(), Vec::new())))
+    }
+
+    /// Records how netcoredbg is about to be spawned, so that a session that fails to start
+    /// can be reproduced from the debug log alone
+    fn log_launch(binary: &DebugAdapterBinary) {
+        let envs: serde_json::Map<String, Value> = binary
+            .envs
+            .iter()
+            .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+            .collect();
+        LOG.info(&format!(
+            "Spawning {} with arguments {:?} in {}; environment additions: {}",
+            binary.command.as_deref().unwrap_or_default(),
+            binary.arguments,
+            binary.cwd.as_deref().unwrap_or_default(),
+            Logger::redacted(&Value::Object(envs))
+        ));
+        let configuration = serde_json::from_str::<Value>(&binary.request_args.configuration)
+            .map(|value| Logger::redacted(&value).to_string())
+            .unwrap_or_else(|_| binary.request_args.configuration.clone());
+        LOG.info(&format!(
+            "Configuration passed to netcoredbg: {}",
+            configuration
+        ));
+    }
+}
+
+impl zed::Extension for NetCoreDbgExtension {
+    fn new() -> Self {
+        Logger::install_panic_hook();
+        Self::default()
+    }
+
+    fn get_dap_binary(
+        &mut self,
+        adapter_name: String,
+        config: DebugTaskDefinition,
+        user_provided_debug_adapter_path: Option<String>,
+        worktree: &Worktree,
+    ) -> Result<DebugAdapterBinary, String> {
+        let session = Logger::begin_session();
+        LOG.info(&format!("Starting debug session '{}'", config.label));
+        self.dap_binary(
+            adapter_name,
+            config,
+            user_provided_debug_adapter_path,
+            worktree,
+        )
+        .map_err(|e| format!("{}\nSession: {}\n{}", e, session.id(), Self::recent_log()))
+    }
+
     fn dap_request_kind(
         &mut self,
         adapter_name: String,
@@ -124,65 +1554,125 @@ impl zed::Extension for NetCoreDbgExtension {
     }
 
     fn dap_config_to_scenario(&mut self, config: DebugConfig) -> Result<DebugScenario, String> {
-        match config.request {
-            DebugRequest::Launch(launch) => {
-                let adapter_config = NetCoreDbgDebugConfig {
-                    request: "launch".to_string(),
-                    program: Some(launch.program),
-                    args: if launch.args.is_empty() {
-                        None
-                    } else {
-                        Some(launch.args)
-                    },
-                    cwd: launch.cwd,
-                    env: launch.envs.into_iter().collect(),
-                    stop_at_entry: config.stop_on_entry,
-                    process_id: None,
-                    just_my_code: None,
-                    enable_step_filtering: None,
-                };
-
-                let config_json = serde_json::to_string(&adapter_config)
-                    .map_err(|e| format!("Failed to serialize launch config: {}", e))?;
-
-                Ok(DebugScenario {
-                    label: config.label,
-                    adapter: config.adapter,
-                    build: None,
-                    config: config_json,
-                    tcp_connection: None,
-                })
-            }
+        let adapter_config = match config.request {
+            DebugRequest::Launch(launch) => NetCoreDbgDebugConfig {
+                request: "launch".to_string(),
+                // Left unset so that the program is located from the project at launch
+                program: Some(launch.program)
+                    .filter(|program| !program.is_empty() && program != AUTO_PROGRAM),
+                args: if launch.args.is_empty() {
+                    None
+                } else {
+                    Some(Value::from(launch.args))
+                },
+                cwd: launch.cwd,
+                env: launch
+                    .envs
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::String(value)))
+                    .collect(),
+                stop_at_entry: config.stop_on_entry,
+                ..Default::default()
+            },
             DebugRequest::Attach(attach) => {
                 let process_id = attach.process_id.ok_or_else(|| {
                     "Attach mode requires a process ID. Please select a process from the attach modal.".to_string()
                 })?;
-
-                let adapter_config = NetCoreDbgDebugConfig {
+                // netcoredbg only stops at the entry point of programs it launches
+                NetCoreDbgDebugConfig {
                     request: "attach".to_string(),
-                    program: None,
-                    args: None,
-                    cwd: None,
-                    env: HashMap::new(),
-                    stop_at_entry: config.stop_on_entry,
                     process_id: Some(ProcessId::Int(process_id as i32)),
-                    just_my_code: None,
-                    enable_step_filtering: None,
-                };
+                    ..Default::default()
+                }
+            }
+        };
 
-                let config_json = serde_json::to_string(&adapter_config)
-                    .map_err(|e| format!("Failed to serialize attach config: {}", e))?;
+        let config_json = serde_json::to_string(&adapter_config).map_err(|e| {
+            format!(
+                "Failed to serialize {} config: {}",
+                adapter_config.request, e
+            )
+        })?;
 
-                Ok(DebugScenario {
-                    label: config.label,
-                    adapter: config.adapter,
-                    build: None,
-                    config: config_json,
-                    tcp_connection: None,
-                })
-            }
+        Ok(DebugScenario {
+            label: config.label,
+            adapter: config.adapter,
+            build: None,
+            config: config_json,
+            tcp_connection: None,
+        })
+    }
+
+    fn dap_locator_create_scenario(
+        &mut self,
+        locator_name: String,
+        build_task: TaskTemplate,
+        resolved_label: String,
+        debug_adapter_name: String,
+    ) -> Option<DebugScenario> {
+        if locator_name != locator::LOCATOR_NAME || debug_adapter_name != Self::ADAPTER_NAME {
+            return None;
         }
+        let config = locator::debug_config(&build_task)?;
+        Some(DebugScenario {
+            label: resolved_label,
+            adapter: debug_adapter_name,
+            // Projects are built by the extension itself through `buildProject`
+            build: None,
+            config: serde_json::to_string(&config).ok()?,
+            tcp_connection: None,
+        })
     }
 }
 
 zed::register_extension!(NetCoreDbgExtension);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: Value) -> NetCoreDbgDebugConfig {
+        serde_json::from_value(json).expect("valid debug configuration")
+    }
+
+    #[test]
+    fn sends_just_my_code_for_launch_and_attach() {
+        let cases = [
+            (serde_json::json!({"request": "launch"}), true),
+            (
+                serde_json::json!({"request": "launch", "justMyCode": true}),
+                true,
+            ),
+            (
+                serde_json::json!({"request": "launch", "justMyCode": false}),
+                false,
+            ),
+            (serde_json::json!({"request": "attach"}), true),
+            (
+                serde_json::json!({"request": "attach", "justMyCode": false}),
+                false,
+            ),
+            // Source Link asks for library code, unless justMyCode is set explicitly
+            (
+                serde_json::json!({"request": "launch", "enableSourceLink": true}),
+                false,
+            ),
+            (
+                serde_json::json!({"request": "launch", "enableSourceLink": true, "justMyCode": true}),
+                true,
+            ),
+        ];
+        for (json, expected) in cases {
+            let request = match json["request"].as_str() {
+                Some("attach") => StartDebuggingRequestArgumentsRequest::Attach,
+                _ => StartDebuggingRequestArgumentsRequest::Launch,
+            };
+            let options = NetCoreDbgExtension::stepping_options(&config(json.clone()), request);
+            assert_eq!(
+                Value::Object(options),
+                serde_json::json!({"justMyCode": expected}),
+                "config: {json}"
+            );
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use crate::logger::{Logger, ScopedLogger};
+use crate::project::BuildMode;
+use crate::NetCoreDbgDebugConfig;
+use std::path::Path;
+use zed_extension_api::serde_json::Value;
+use zed_extension_api::TaskTemplate;
+
+const LOG: ScopedLogger = Logger::scoped("locator");
+
+/// Name of the locator declared in `extension.toml`
+pub const LOCATOR_NAME: &str = "dotnet";
+
+/// `dotnet run` options followed by a value. Other options are flags, and arguments `dotnet run`
+/// does not recognize are passed on to the application.
+const RUN_VALUE_OPTIONS: [&str; 16] = [
+    "-p",
+    "--project",
+    "-c",
+    "--configuration",
+    "-f",
+    "--framework",
+    "-r",
+    "--runtime",
+    "-a",
+    "--arch",
+    "--os",
+    "-lp",
+    "--launch-profile",
+    "-v",
+    "--verbosity",
+    "--property",
+];
+
+/// Converts a task running .NET code into a debug configuration, or returns `None` for tasks
+/// this locator does not recognize:
+/// - `dotnet run` becomes a launch of the project's build output, built before launching
+/// - `dotnet App.dll` and `dotnet exec App.dll` launch the assembly as is
+/// - `dotnet test` becomes an attach to the test host it starts with `VSTEST_HOST_DEBUG=1`
+pub fn debug_config(task: &TaskTemplate) -> Option<NetCoreDbgDebugConfig> {
+    if !is_dotnet(&task.command) {
+        return None;
+    }
+    let (subcommand, rest) = task.args.split_first()?;
+    let config = match subcommand.as_str() {
+        "run" => run_config(task, rest),
+        "test" => NetCoreDbgDebugConfig {
+            request: "attach".to_string(),
+            ..Default::default()
+        },
+        "exec" => assembly_config(task, rest)?,
+        assembly if assembly.to_ascii_lowercase().ends_with(".dll") => {
+            assembly_config(task, &task.args)?
+        }
+        _ => return None,
+    };
+    LOG.info(&format!(
+        "Debugging task '{}' ({} {}) as a {} configuration",
+        task.label,
+        task.command,
+        task.args.join(" "),
+        config.request
+    ));
+    Some(config)
+}
+
+fn is_dotnet(command: &str) -> bool {
+    let name = command.rsplit(['/', '\\']).next().unwrap_or(command);
+    name.eq_ignore_ascii_case("dotnet") || name.eq_ignore_ascii_case("dotnet.exe")
+}
+
+/// A launch of the project `dotnet run` would run, with its options and the application
+/// arguments following `--` or not recognized by `dotnet run`
+fn run_config(task: &TaskTemplate, args: &[String]) -> NetCoreDbgDebugConfig {
+    let (run_args, app_args) = split_run_args(args);
+    let mut config = task_config(task);
+    config.build_project = BuildMode::Always;
+
+    let mut run_args = run_args.iter();
+    while let Some(arg) = run_args.next() {
+        // Options may also be written as `--option=value` or `--property:Name=Value`
+        let (option, inline_value) = match arg.split_once(['=', ':']) {
+            Some((option, value)) if option.starts_with('-') => (option, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let takes_value = RUN_VALUE_OPTIONS.contains(&option);
+        let value = match inline_value {
+            Some(value) => Some(value),
+            None if takes_value => run_args.next().cloned(),
+            None => None,
+        };
+        match (option, value) {
+            ("-p" | "--project", Some(project)) => {
+                config.project = Some(task_path(task, &project));
+            }
+            ("-c" | "--configuration", Some(configuration)) => {
+                config.configuration = Some(configuration);
+            }
+            ("-f" | "--framework", Some(framework)) => config.target_framework = Some(framework),
+            ("-lp" | "--launch-profile", Some(profile)) => config.launch_profile = Some(profile),
+            ("--no-build", None) => config.build_project = BuildMode::Never,
+            // `dotnet run` accepts the project file as a positional argument too
+            (project, None) if project.to_ascii_lowercase().ends_with(".csproj") => {
+                config.project = Some(task_path(task, project));
+            }
+            (option, _) => LOG.debug(&format!("Ignoring dotnet run option '{}'", option)),
+        }
+    }
+
+    if !app_args.is_empty() {
+        config.args = Some(Value::from(app_args));
+    }
+    config
+}
+
+/// Splits `dotnet run` arguments into its own options and those of the application: all
+/// arguments after `--`, and before it those `dotnet run` does not know
+fn split_run_args(args: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut run_args = Vec::new();
+    let mut app_args = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            app_args.extend(args.by_ref().cloned());
+            break;
+        }
+        let option = arg
+            .split_once(['=', ':'])
+            .map_or(arg.as_str(), |(option, _)| option);
+        if RUN_VALUE_OPTIONS.contains(&option) {
+            run_args.push(arg.clone());
+            if !arg.contains(['=', ':']) {
+                run_args.extend(args.next().cloned());
+            }
+        } else if arg.starts_with('-') || arg.to_ascii_lowercase().ends_with(".csproj") {
+            run_args.push(arg.clone());
+        } else {
+            app_args.push(arg.clone());
+        }
+    }
+    (run_args, app_args)
+}
+
+/// A launch of the assembly passed to `dotnet` or `dotnet exec`, with the arguments after it.
+/// Host options of `dotnet exec` before the assembly are skipped.
+fn assembly_config(task: &TaskTemplate, args: &[String]) -> Option<NetCoreDbgDebugConfig> {
+    let index = args
+        .iter()
+        .position(|arg| arg.to_ascii_lowercase().ends_with(".dll"))?;
+    let mut config = task_config(task);
+    config.program = Some(task_path(task, &args[index]));
+    if index + 1 < args.len() {
+        config.args = Some(Value::from(args[index + 1..].to_vec()));
+    }
+    Some(config)
+}
+
+/// A launch configuration with the task's working directory and environment
+fn task_config(task: &TaskTemplate) -> NetCoreDbgDebugConfig {
+    NetCoreDbgDebugConfig {
+        request: "launch".to_string(),
+        cwd: task.cwd.clone(),
+        env: task
+            .env
+            .iter()
+            .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Resolves a path of the task's command line against its working directory
+fn task_path(task: &TaskTemplate, path: &str) -> String {
+    match &task.cwd {
+        Some(cwd) if !crate::paths::is_absolute_path(path) => {
+            Path::new(cwd).join(path).to_string_lossy().into_owned()
+        }
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed_extension_api::serde_json;
+
+    fn task(command: &str, args: &[&str]) -> TaskTemplate {
+        TaskTemplate {
+            label: "task".to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            env: vec![("ASPNETCORE_URLS".to_string(), "http://+:5001".to_string())],
+            cwd: Some("/src".to_string()),
+        }
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_dotnet_run_arguments() {
+        let cases: [(&[&str], &[&str], &[&str]); 6] = [
+            (
+                &["--project", "X", "--", "--appArg"],
+                &["--project", "X"],
+                &["--appArg"],
+            ),
+            (
+                &[
+                    "--project=App/App.csproj",
+                    "-c",
+                    "Release",
+                    "--",
+                    "-c",
+                    "Debug",
+                ],
+                &["--project=App/App.csproj", "-c", "Release"],
+                &["-c", "Debug"],
+            ),
+            (
+                &["App.csproj", "--no-build", "input.txt"],
+                &["App.csproj", "--no-build"],
+                &["input.txt"],
+            ),
+            (
+                &["--property:Foo=Bar", "-lp", "https"],
+                &["--property:Foo=Bar", "-lp", "https"],
+                &[],
+            ),
+            (&["--", "--", "x"], &[], &["--", "x"]),
+            (&[], &[], &[]),
+        ];
+        for (args, run_args, app_args) in cases {
+            assert_eq!(
+                split_run_args(&strings(args)),
+                (strings(run_args), strings(app_args)),
+                "args: {args:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn launches_the_project_of_dotnet_run() {
+        let config = debug_config(&task(
+            "dotnet",
+            &[
+                "run",
+                "--project",
+                "App/App.csproj",
+                "-c",
+                "Release",
+                "--framework=net8.0",
+                "--launch-profile",
+                "https",
+                "--",
+                "--appArg",
+                "value",
+            ],
+        ))
+        .unwrap();
+        assert_eq!(config.request, "launch");
+        assert_eq!(config.project.as_deref(), Some("/src/App/App.csproj"));
+        assert_eq!(config.configuration.as_deref(), Some("Release"));
+        assert_eq!(config.target_framework.as_deref(), Some("net8.0"));
+        assert_eq!(config.launch_profile.as_deref(), Some("https"));
+        assert_eq!(config.build_project, BuildMode::Always);
+        assert_eq!(config.args, Some(serde_json::json!(["--appArg", "value"])));
+        assert_eq!(config.cwd.as_deref(), Some("/src"));
+        assert_eq!(
+            config.env.get("ASPNETCORE_URLS"),
+            Some(&Value::String("http://+:5001".to_string()))
+        );
+
+        let config = debug_config(&task("/usr/bin/dotnet", &["run", "--no-build"])).unwrap();
+        assert_eq!(config.build_project, BuildMode::Never);
+        assert_eq!(config.args, None);
+    }
+
+    #[test]
+    fn launches_assemblies_and_attaches_to_tests() {
+        let config = debug_config(&task("dotnet.exe", &["bin/App.dll", "--port", "5001"])).unwrap();
+        assert_eq!(config.program.as_deref(), Some("/src/bin/App.dll"));
+        assert_eq!(config.args, Some(serde_json::json!(["--port", "5001"])));
+
+        let config = debug_config(&task(
+            "dotnet",
+            &[
+                "exec",
+                "--runtimeconfig",
+                "App.runtimeconfig.json",
+                "/out/App.dll",
+            ],
+        ))
+        .unwrap();
+        assert_eq!(config.program.as_deref(), Some("/out/App.dll"));
+        assert_eq!(config.args, None);
+
+        let config = debug_config(&task("dotnet", &["test"])).unwrap();
+        assert_eq!(config.request, "attach");
+    }
+
+    #[test]
+    fn ignores_other_tasks() {
+        assert!(debug_config(&task("cargo", &["run"])).is_none());
+        assert!(debug_config(&task("dotnet", &["build"])).is_none());
+        assert!(debug_config(&task("dotnet", &[])).is_none());
+        assert!(debug_config(&task("dotnet", &["exec", "--help"])).is_none());
+    }
+}
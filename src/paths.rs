@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+/// Expands a leading `~`/`~user` to a home directory and substitutes `$VAR`, `${VAR}` and
+/// `%VAR%` references using `env`. Unknown variables are reported as errors naming the
+/// variable instead of being passed through literally.
+pub fn expand_path(input: &str, env: &HashMap<String, String>) -> Result<String, String> {
+    let with_vars = expand_env_vars(input, env)?;
+    expand_tilde(&with_vars, env)
+}
+
+/// Whether `path` is absolute on any host platform. The extension runs under WASI, where
+/// `std::path::Path` only recognizes `/`-rooted paths, so Windows drive-letter and UNC paths
+/// are detected from the string instead.
+pub fn is_absolute_path(path: &str) -> bool {
+    path.starts_with(['/', '\\']) || has_drive_letter(path)
+}
+
+/// Longest path most Windows APIs accept without the `\\?\` verbatim prefix
+pub const WINDOWS_MAX_PATH: usize = 260;
+
+/// Converts a path as seen by the WASI sandbox into one Windows programs can open: the `/`
+/// WASI puts before drive letters is dropped, separators become `\`, and the leading `\\` of
+/// UNC (`\\server\share`) and verbatim (`\\?\`) paths is preserved while other repeated
+/// separators are collapsed.
+pub fn normalize_windows_path(path: &str) -> String {
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => &path[1..],
+        _ => path,
+    };
+    let is_unc = path.len() > 2
+        && path.starts_with(['/', '\\'])
+        && path[1..].starts_with(['/', '\\'])
+        && !path[2..].starts_with(['/', '\\']);
+
+    let mut normalized = String::with_capacity(path.len());
+    if is_unc {
+        normalized.push('\\');
+    }
+    for c in path.chars() {
+        let c = if c == '/' { '\\' } else { c };
+        if c == '\\' && normalized.ends_with('\\') && normalized.len() > usize::from(is_unc) {
+            continue;
+        }
+        normalized.push(c);
+    }
+    normalized
+}
+
+/// Prefixes a normalized Windows path longer than [`WINDOWS_MAX_PATH`] with `\\?\` (or
+/// `\\?\UNC\` for network shares) so that it can still be opened and spawned. Shorter and
+/// already verbatim paths are returned unchanged.
+pub fn to_long_windows_path(path: &str) -> String {
+    if path.len() < WINDOWS_MAX_PATH || path.starts_with(r"\\?\") {
+        return path.to_string();
+    }
+    match path.strip_prefix(r"\\") {
+        Some(share) => format!(r"\\?\UNC\{}", share),
+        None if has_drive_letter(path) => format!(r"\\?\{}", path),
+        // Relative paths cannot be made verbatim
+        None => path.to_string(),
+    }
+}
+
+/// Translates a Windows path into the path WSL distributions see it at: drive paths are
+/// mounted under `/mnt/<drive>` and `\\wsl$\<distribution>` (or `\\wsl.localhost\...`) paths
+/// point into the distribution's own file system. Returns `None` for other paths, such as
+/// relative or Linux paths.
+pub fn to_wsl_path(path: &str) -> Option<String> {
+    let path = normalize_windows_path(path);
+    if has_drive_letter(&path) {
+        let drive = path[..1].to_ascii_lowercase();
+        let rest = path[2..].replace('\\', "/");
+        return Some(format!("/mnt/{}/{}", drive, rest.trim_start_matches('/')));
+    }
+    let share = path.strip_prefix(r"\\")?;
+    let (host, rest) = share.split_once('\\')?;
+    if !host.eq_ignore_ascii_case("wsl$") && !host.eq_ignore_ascii_case("wsl.localhost") {
+        return None;
+    }
+    // Skip the distribution name
+    let rest = rest.split_once('\\').map_or("", |(_, rest)| rest);
+    Some(format!("/{}", rest.replace('\\', "/")))
+}
+
+fn has_drive_letter(path: &str) -> bool {
+    matches!(path.as_bytes(), [drive, b':', ..] if drive.is_ascii_alphabetic())
+}
+
+fn lookup<'a>(name: &str, env: &'a HashMap<String, String>) -> Option<&'a String> {
+    // Windows environment variable names are case-insensitive
+    env.get(name).or_else(|| {
+        env.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    })
+}
+
+fn home_dir(env: &HashMap<String, String>) -> Option<&String> {
+    lookup("HOME", env).or_else(|| lookup("USERPROFILE", env))
+}
+
+fn expand_tilde(input: &str, env: &HashMap<String, String>) -> Result<String, String> {
+    let Some(rest) = input.strip_prefix('~') else {
+        return Ok(input.to_string());
+    };
+
+    let split_at = rest.find(['/', '\\']).unwrap_or(rest.len());
+    let (user, remainder) = rest.split_at(split_at);
+
+    let home = home_dir(env).ok_or_else(|| {
+        format!(
+            "Cannot expand '~' in '{}': neither HOME nor USERPROFILE is set",
+            input
+        )
+    })?;
+
+    if user.is_empty() {
+        return Ok(format!("{}{}", home, remainder));
+    }
+
+    // `~user` resolves to a sibling of the current user's home directory (e.g. /home/user)
+    let home = home.trim_end_matches(['/', '\\']);
+    let parent_end = home.rfind(['/', '\\']).ok_or_else(|| {
+        format!(
+            "Cannot expand '~{}' in '{}': unable to determine home directories",
+            user, input
+        )
+    })?;
+    Ok(format!("{}{}{}", &home[..=parent_end], user, remainder))
+}
+
+fn is_var_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_var_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn expand_env_vars(input: &str, env: &HashMap<String, String>) -> Result<String, String> {
+    let resolve = |name: &str| {
+        lookup(name, env).cloned().ok_or_else(|| {
+            format!(
+                "Unknown environment variable '{}' in path '{}'",
+                name, input
+            )
+        })
+    };
+
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(index) = rest.find(['$', '%']) {
+        output.push_str(&rest[..index]);
+        let marker = &rest[index..];
+
+        if let Some(braced) = marker.strip_prefix("${") {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| format!("Unterminated '${{' in path '{}'", input))?;
+            output.push_str(&resolve(&braced[..end])?);
+            rest = &braced[end + 1..];
+        } else if let Some(after) = marker.strip_prefix('$') {
+            let name_len = if after.starts_with(is_var_start) {
+                after.find(|c| !is_var_char(c)).unwrap_or(after.len())
+            } else {
+                0
+            };
+            if name_len == 0 {
+                output.push('$');
+            } else {
+                output.push_str(&resolve(&after[..name_len])?);
+            }
+            rest = &after[name_len..];
+        } else {
+            let after = &marker[1..];
+            match after.find('%') {
+                Some(end)
+                    if end > 0
+                        && after.starts_with(is_var_start)
+                        && after[..end].chars().all(is_var_char) =>
+                {
+                    output.push_str(&resolve(&after[..end])?);
+                    rest = &after[end + 1..];
+                }
+                _ => {
+                    output.push('%');
+                    rest = after;
+                }
+            }
+        }
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_windows_paths() {
+        let cases = [
+            // WASI puts a `/` before drive letters
+            ("/C:/Users/me/app.dll", r"C:\Users\me\app.dll"),
+            ("C:/Users/me", r"C:\Users\me"),
+            // Drive letter case is kept as written
+            ("/c:/users/me", r"c:\users\me"),
+            ("d:\\src", r"d:\src"),
+            // Trailing separators are kept, but collapsed
+            (r"C:\src\", r"C:\src\"),
+            ("C:/src//", r"C:\src\"),
+            (r"C:\\src\\\app", r"C:\src\app"),
+            // UNC and verbatim prefixes keep their leading `\\`
+            ("//wsl$/Ubuntu/home/me", r"\\wsl$\Ubuntu\home\me"),
+            (r"\\server\share\\dir", r"\\server\share\dir"),
+            (r"\\?\C:\very\long", r"\\?\C:\very\long"),
+            (r"\\?\UNC\server\share", r"\\?\UNC\server\share"),
+            // Relative paths only get their separators converted
+            ("bin/Debug//net8.0", r"bin\Debug\net8.0"),
+            ("", ""),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalize_windows_path(input), expected, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn prefixes_long_windows_paths() {
+        let long_tail = "a".repeat(WINDOWS_MAX_PATH);
+        let cases = [
+            (r"C:\short".to_string(), r"C:\short".to_string()),
+            (format!(r"C:\{long_tail}"), format!(r"\\?\C:\{long_tail}")),
+            (
+                format!(r"\\server\share\{long_tail}"),
+                format!(r"\\?\UNC\server\share\{long_tail}"),
+            ),
+            (
+                format!(r"\\?\C:\{long_tail}"),
+                format!(r"\\?\C:\{long_tail}"),
+            ),
+            (format!(r"bin\{long_tail}"), format!(r"bin\{long_tail}")),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(to_long_windows_path(&input), expected, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn translates_paths_for_wsl() {
+        let cases = [
+            (r"C:\Users\me\app.dll", Some("/mnt/c/Users/me/app.dll")),
+            ("c:/users/me/", Some("/mnt/c/users/me/")),
+            ("/D:/src", Some("/mnt/d/src")),
+            ("C:", Some("/mnt/c/")),
+            (r"\\wsl$\Ubuntu\home\me", Some("/home/me")),
+            ("//wsl$/Ubuntu/home/me/", Some("/home/me/")),
+            (r"\\wsl.localhost\Debian\srv", Some("/srv")),
+            (r"\\WSL$\Ubuntu", Some("/")),
+            (r"\\server\share\dir", None),
+            ("/home/me", None),
+            (r"bin\Debug", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(to_wsl_path(input).as_deref(), expected, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn detects_absolute_paths() {
+        let cases = [
+            ("/home/me", true),
+            (r"C:\src", true),
+            ("c:/src", true),
+            (r"\\server\share", true),
+            (r"\\?\C:\src", true),
+            ("bin/Debug", false),
+            ("~/src", false),
+            ("", false),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(is_absolute_path(input), expected, "input: {input}");
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::path::Path;
+use zed_extension_api::process::Command;
+use zed_extension_api::{self as zed};
+
+/// The OS and architecture Zed runs on. Unit tests run natively, outside the extension host,
+/// so they see a fixed Linux x64 platform instead of calling into it.
+pub fn current() -> (zed::Os, zed::Architecture) {
+    #[cfg(not(test))]
+    return zed::current_platform();
+    #[cfg(test)]
+    return (zed::Os::Linux, zed::Architecture::X8664);
+}
+
+/// The OS Zed runs on
+pub fn os() -> zed::Os {
+    current().0
+}
+
+/// A command running the executable `name` found in `dir`. Capabilities match the command name
+/// exactly, so a located executable is run by name with its directory first on `PATH` rather
+/// than by its full path. `env` is passed on, with its `PATH` kept after `dir`.
+pub fn command_in(dir: &Path, name: &str, env: &[(String, String)]) -> Command {
+    let separator = match os() {
+        zed::Os::Windows => ";",
+        _ => ":",
+    };
+    let path = std::iter::once(dir.to_string_lossy().into_owned())
+        .chain(
+            env.iter()
+                .filter(|(key, _)| key.eq_ignore_ascii_case("PATH"))
+                .map(|(_, value)| value.clone()),
+        )
+        .collect::<Vec<_>>()
+        .join(separator);
+    Command::new(name)
+        .envs(
+            env.iter()
+                .filter(|(key, _)| !key.eq_ignore_ascii_case("PATH"))
+                .cloned(),
+        )
+        .env("PATH", path)
+}
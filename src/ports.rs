@@ -0,0 +1,179 @@
+use crate::logger::{Logger, ScopedLogger};
+use crate::platform;
+use zed_extension_api::process::Command;
+use zed_extension_api::{self as zed};
+
+const LOG: ScopedLogger = Logger::scoped("ports");
+
+/// Variables ASP.NET Core reads the URLs to listen on from, in order of precedence
+pub const URL_VARS: [&str; 2] = ["ASPNETCORE_URLS", "DOTNET_URLS"];
+
+/// Variables listing bare ports ASP.NET Core 8 and later listen on, on all interfaces
+pub const PORT_VARS: [&str; 2] = ["ASPNETCORE_HTTP_PORTS", "ASPNETCORE_HTTPS_PORTS"];
+
+/// URL Kestrel listens on when nothing else is configured
+pub const DEFAULT_URL: &str = "http://localhost:5000";
+
+/// A process listening on a TCP port
+pub struct Listener {
+    pub pid: u32,
+    /// Process name, when the platform reports it
+    pub name: Option<String>,
+}
+
+/// The ports of a `;`-separated URL list such as `https://localhost:5001;http://*:5000`.
+/// URLs without a port use the scheme's default, and port 0 asks for a dynamic port, which
+/// cannot conflict.
+pub fn url_ports(urls: &str) -> Vec<u16> {
+    urls.split(';')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .filter_map(|url| {
+            let (scheme, rest) = url.split_once("://")?;
+            let authority = rest.split('/').next().unwrap_or(rest);
+            // IPv6 hosts are bracketed, e.g. http://[::1]:5000
+            let port = match authority.rsplit_once(':') {
+                Some((host, port)) if !port.contains(']') && !host.is_empty() => port.parse().ok(),
+                _ if scheme.eq_ignore_ascii_case("https") => Some(443),
+                _ => Some(80),
+            };
+            port.filter(|&port| port != 0)
+        })
+        .collect()
+}
+
+/// The ports of a `;`-separated port list such as `8080;8081`
+pub fn listed_ports(ports: &str) -> Vec<u16> {
+    ports
+        .split(';')
+        .filter_map(|port| port.trim().parse().ok())
+        .filter(|&port| port != 0)
+        .collect()
+}
+
+/// The process listening on `port`, if any, or `None` when the platform's tools (`lsof`, or
+/// `netstat` on Windows) are unavailable and it cannot be told
+pub fn listener(port: u16) -> Option<Option<Listener>> {
+    match platform::os() {
+        zed::Os::Windows => windows_listener(port),
+        zed::Os::Mac | zed::Os::Linux => unix_listener(port),
+    }
+}
+
+/// The `lsof` arguments selecting the listener on `port`. The address is a separate argument,
+/// as the `process:exec` capability only matches whole arguments with `*`.
+fn lsof_args(port: u16) -> [String; 5] {
+    [
+        "-nP".to_string(),
+        "-i".to_string(),
+        format!("TCP:{}", port),
+        "-sTCP:LISTEN".to_string(),
+        "-Fpc".to_string(),
+    ]
+}
+
+/// Asks `lsof` for the listener, printing fields prefixed with `p` (PID) and `c` (command)
+fn unix_listener(port: u16) -> Option<Option<Listener>> {
+    let output = Command::new("lsof")
+        .args(lsof_args(port))
+        .output()
+        .map_err(|e| LOG.debug(&format!("Cannot run lsof: {}", e)))
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let pid = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix('p'))
+        .and_then(|pid| pid.parse().ok());
+    let name = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix('c'))
+        .map(str::to_string);
+    match pid {
+        Some(pid) => Some(Some(Listener { pid, name })),
+        // lsof exits with 1 when nothing matches
+        None if output.status == Some(1) || output.status == Some(0) => Some(None),
+        None => None,
+    }
+}
+
+/// Finds the listener in `netstat -ano` output and its name with `tasklist`
+fn windows_listener(port: u16) -> Option<Option<Listener>> {
+    let output = Command::new("netstat")
+        .args(["-ano", "-p", "TCP"])
+        .output()
+        .map_err(|e| LOG.debug(&format!("Cannot run netstat: {}", e)))
+        .ok()?;
+    // e.g. `  TCP    0.0.0.0:5000    0.0.0.0:0    LISTENING    1234`
+    let suffix = format!(":{}", port);
+    let pid = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                [_, local, _, "LISTENING", pid] if local.ends_with(&suffix) => pid.parse().ok(),
+                _ => None,
+            }
+        })
+        .next();
+    let Some(pid) = pid else {
+        return Some(None);
+    };
+    let name = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .output()
+        .ok()
+        .and_then(|output| {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let name = stdout
+                .trim()
+                .strip_prefix('"')?
+                .split('"')
+                .next()?
+                .to_string();
+            Some(name)
+        });
+    Some(Some(Listener { pid, name }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zed_extension_api::serde_json;
+
+    /// The `args` patterns of the `process:exec` capabilities `extension.toml` declares for
+    /// `command`
+    fn declared_args(command: &str) -> Vec<Vec<String>> {
+        let manifest = include_str!("../extension.toml");
+        let command_line = format!("command = \"{}\"", command);
+        let lines: Vec<&str> = manifest.lines().collect();
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.trim() == command_line)
+            .filter_map(|(i, _)| lines.get(i + 1)?.trim().strip_prefix("args = "))
+            // The manifest's arrays of plain strings are valid JSON
+            .map(|args| serde_json::from_str(args).expect("args are an array of strings"))
+            .collect()
+    }
+
+    fn matches(pattern: &[String], args: &[String]) -> bool {
+        pattern.len() == args.len()
+            && pattern
+                .iter()
+                .zip(args)
+                .all(|(pattern, arg)| pattern == "*" || pattern == arg)
+    }
+
+    #[test]
+    fn runs_lsof_with_declared_arguments() {
+        let declared = declared_args("lsof");
+        assert!(!declared.is_empty(), "lsof is declared in extension.toml");
+        for port in [80, 5001, 65535] {
+            let args = lsof_args(port);
+            assert!(
+                declared.iter().any(|pattern| matches(pattern, &args)),
+                "args: {args:?}, declared: {declared:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,452 @@
+use crate::platform;
+use zed_extension_api::process::Command;
+use zed_extension_api::{self as zed};
+
+/// Value of `processId` asking the extension to look up the process to attach to
+pub const PICK_PROCESS: &str = "${command:pickProcess}";
+
+/// `dotnet` subcommands that run SDK tooling rather than an application
+const SDK_COMMANDS: [&str; 12] = [
+    "build", "clean", "format", "msbuild", "new", "pack", "publish", "restore", "run", "test",
+    "tool", "watch",
+];
+
+/// A running process that looks like a .NET application
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotnetProcess {
+    pub pid: u32,
+    pub name: String,
+    pub command_line: String,
+}
+
+/// Lists the running processes that look like .NET applications: the `dotnet` host running an
+/// application, processes whose command line references a managed `.dll`, and apphost
+/// executables in a `bin/<configuration>/<framework>` output directory. SDK tooling such as
+/// MSBuild or `dotnet watch` is left out. `tasklist` does not report command lines, so on
+/// Windows only `dotnet.exe` processes are found.
+pub fn list_dotnet_processes() -> Result<Vec<DotnetProcess>, String> {
+    Ok(list_processes()?
+        .into_iter()
+        .filter(is_dotnet_process)
+        .collect())
+}
+
+/// The running processes named `name`, newest first: executables with that file name, with
+/// or without `.exe`, and the `dotnet` host running `<name>.dll`
+pub fn named(name: &str) -> Result<Vec<DotnetProcess>, String> {
+    let name = stem(name);
+    let mut processes: Vec<DotnetProcess> = list_processes()?
+        .into_iter()
+        .filter(|process| {
+            let name_matches = |path: &str| stem(path).eq_ignore_ascii_case(name);
+            let host_runs_name = || {
+                let mut args = process.command_line.split_whitespace().skip(1);
+                matches!(
+                    process.name.to_ascii_lowercase().as_str(),
+                    "dotnet" | "dotnet.exe"
+                ) && args.next().is_some_and(name_matches)
+            };
+            name_matches(&process.name) || host_runs_name()
+        })
+        .collect();
+    sort_newest_first(&mut processes);
+    Ok(processes)
+}
+
+fn list_processes() -> Result<Vec<DotnetProcess>, String> {
+    let processes = match platform::os() {
+        zed::Os::Windows => {
+            let output = Command::new("tasklist")
+                .args(["/FO", "CSV", "/NH"])
+                .output()
+                .map_err(|e| format!("Failed to run tasklist: {}", e))?;
+            parse_tasklist(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => {
+            let output = Command::new("ps")
+                .args(["-axww", "-o", "pid=,args="])
+                .output()
+                .map_err(|e| format!("Failed to run ps: {}", e))?;
+            parse_ps(&String::from_utf8_lossy(&output.stdout))
+        }
+    };
+    Ok(processes)
+}
+
+/// Orders `processes` by how long they have been running, as `ps` reports it. `tasklist`
+/// reports no start times, so on Windows, or when `ps` fails, higher PIDs are taken as newer.
+fn sort_newest_first(processes: &mut [DotnetProcess]) {
+    processes.sort_by_key(|process| std::cmp::Reverse(process.pid));
+    if processes.len() < 2 || platform::os() == zed::Os::Windows {
+        return;
+    }
+    let pids: Vec<String> = processes
+        .iter()
+        .map(|process| process.pid.to_string())
+        .collect();
+    let Ok(output) = Command::new("ps")
+        .args(["-o", "pid=,etime=", "-p", &pids.join(",")])
+        .output()
+    else {
+        return;
+    };
+    let elapsed: Vec<(u32, u64)> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some((fields.next()?.parse().ok()?, parse_etime(fields.next()?)?))
+        })
+        .collect();
+    processes.sort_by_key(|process| {
+        elapsed
+            .iter()
+            .find(|(pid, _)| *pid == process.pid)
+            .map_or(u64::MAX, |(_, elapsed)| *elapsed)
+    });
+}
+
+/// Parses the `[[dd-]hh:]mm:ss` elapsed time `ps` prints into seconds
+fn parse_etime(etime: &str) -> Option<u64> {
+    let (days, time) = match etime.split_once('-') {
+        Some((days, time)) => (days.parse::<u64>().ok()?, time),
+        None => (0, etime),
+    };
+    let mut seconds = 0;
+    for part in time.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(days * 24 * 60 * 60 + seconds)
+}
+
+/// The processes among `processes` whose command line references a path below `dir`, such as
+/// the build output of a project in the worktree. Separators are ignored, and so is case for
+/// Windows drive paths.
+pub fn running_from<'a>(processes: &'a [DotnetProcess], dir: &str) -> Vec<&'a DotnetProcess> {
+    // WASI may report Windows paths as /C:/...
+    let dir = match dir.as_bytes() {
+        [b'/', _, b':', ..] => &dir[1..],
+        _ => dir,
+    };
+    let case_insensitive = matches!(dir.as_bytes(), [_, b':', ..]);
+    let normalize = |path: &str| {
+        let path = path.replace('\\', "/");
+        if case_insensitive {
+            path.to_ascii_lowercase()
+        } else {
+            path
+        }
+    };
+    let dir = format!("{}/", normalize(dir).trim_end_matches('/'));
+    if dir == "/" {
+        return Vec::new();
+    }
+    processes
+        .iter()
+        .filter(|process| normalize(&process.command_line).contains(&dir))
+        .collect()
+}
+
+/// Parses `ps -o pid=,args=` output: one process per line, the PID followed by the command
+/// line. Header lines, which some `ps` implementations print anyway, and lines without a
+/// command are skipped.
+fn parse_ps(output: &str) -> Vec<DotnetProcess> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (pid, command_line) = line.trim_start().split_once(char::is_whitespace)?;
+            let command_line = command_line.trim().to_string();
+            let executable = command_line.split_whitespace().next()?;
+            Some(DotnetProcess {
+                pid: pid.parse().ok()?,
+                name: file_name(executable).to_string(),
+                command_line,
+            })
+        })
+        .collect()
+}
+
+/// Parses `tasklist /FO CSV /NH` output: quoted image name, PID, session name, session number
+/// and memory usage per line. Header lines, localized and thus recognized by their PID not
+/// being a number, and lines cut off before their last field are skipped.
+fn parse_tasklist(output: &str) -> Vec<DotnetProcess> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().strip_prefix('"')?.strip_suffix('"')?;
+            let fields: Vec<&str> = line.split("\",\"").collect();
+            let [name, pid, _, _, _] = fields.as_slice() else {
+                return None;
+            };
+            let name = name.to_string();
+            let pid = pid.parse().ok()?;
+            Some(DotnetProcess {
+                pid,
+                command_line: name.clone(),
+                name,
+            })
+        })
+        .collect()
+}
+
+fn is_dotnet_process(process: &DotnetProcess) -> bool {
+    let mut args = process.command_line.split_whitespace();
+    let executable = args.next().unwrap_or_default().replace('\\', "/");
+    let args: Vec<&str> = args.collect();
+
+    if executable.contains("/sdk/")
+        || args
+            .iter()
+            .any(|arg| arg.replace('\\', "/").contains("/sdk/"))
+    {
+        return false;
+    }
+    let name = process.name.to_ascii_lowercase();
+    if name == "dotnet" || name == "dotnet.exe" {
+        return args
+            .first()
+            .is_none_or(|command| !SDK_COMMANDS.contains(command));
+    }
+    // e.g. App/bin/Debug/net8.0/App
+    let is_apphost = executable
+        .rsplit('/')
+        .nth(1)
+        .is_some_and(|framework| framework.starts_with("net"))
+        && executable.rsplit('/').nth(3) == Some("bin");
+    is_apphost
+        || args
+            .iter()
+            .any(|arg| arg.to_ascii_lowercase().ends_with(".dll"))
+}
+
+/// A process that is started by tooling and may wait for a debugger to attach
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitingProcess {
+    /// A VSTest test host, which `dotnet test` starts to run the tests and which waits for a
+    /// debugger when `VSTEST_HOST_DEBUG=1` is set
+    TestHost,
+    /// The isolated worker of an Azure Functions app, which the Functions host starts with the
+    /// options of its gRPC channel and which waits for a debugger with
+    /// `--dotnet-isolated-debug`
+    FunctionsWorker,
+}
+
+impl WaitingProcess {
+    pub fn of(process: &DotnetProcess) -> Option<Self> {
+        let args = || process.command_line.split_whitespace();
+        if args().any(|arg| file_name(arg).to_ascii_lowercase().starts_with("testhost")) {
+            Some(Self::TestHost)
+        } else if args().any(|arg| arg == "--functions-worker-id" || arg == "--workerId") {
+            Some(Self::FunctionsWorker)
+        } else {
+            None
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::TestHost => "test host",
+            Self::FunctionsWorker => "Azure Functions worker",
+        }
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// The file name of `path` without an `.exe` or `.dll` extension
+fn stem(path: &str) -> &str {
+    let name = file_name(path);
+    match name.len().checked_sub(4).and_then(|at| name.get(at..)) {
+        Some(extension)
+            if extension.eq_ignore_ascii_case(".exe") || extension.eq_ignore_ascii_case(".dll") =>
+        {
+            &name[..name.len() - 4]
+        }
+        _ => name,
+    }
+}
+
+/// Whether a process with the given ID is running, or `None` if that cannot be determined,
+/// e.g. because `ps`/`tasklist` is unavailable. The WASI sandbox has no access to `/proc`, so
+/// the platform's process listing tool is asked instead.
+pub fn process_exists(pid: u32) -> Option<bool> {
+    let pid = pid.to_string();
+    match platform::os() {
+        zed::Os::Windows => {
+            let output = Command::new("tasklist")
+                .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+                .output()
+                .ok()?;
+            if output.status != Some(0) {
+                return None;
+            }
+            let quoted_pid = format!("\"{}\"", pid);
+            Some(
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .any(|line| line.split(',').nth(1) == Some(quoted_pid.as_str())),
+            )
+        }
+        _ => {
+            // `ps` exits with 1 when no process matches and with other codes on usage errors
+            let output = Command::new("ps")
+                .args(["-p", &pid, "-o", "pid="])
+                .output()
+                .ok()?;
+            match output.status {
+                Some(0) => Some(true),
+                Some(1) => Some(false),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, command_line: &str) -> DotnetProcess {
+        let executable = command_line.split_whitespace().next().unwrap_or_default();
+        DotnetProcess {
+            pid,
+            name: file_name(executable).to_string(),
+            command_line: command_line.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_ps_output() {
+        let output = concat!(
+            "  PID ARGS\n",
+            "    1 /sbin/init splash\n",
+            "  812 /usr/share/dotnet/dotnet /src/App/bin/Debug/net8.0/App.dll --urls http://+:5001\n",
+            "\n",
+            "10433 \n",
+            "10434\n",
+            "10435 /src/Web/bin/Debug/net8.0/Web",
+        );
+        assert_eq!(
+            parse_ps(output),
+            [
+                process(1, "/sbin/init splash"),
+                process(
+                    812,
+                    "/usr/share/dotnet/dotnet /src/App/bin/Debug/net8.0/App.dll --urls http://+:5001"
+                ),
+                process(10435, "/src/Web/bin/Debug/net8.0/Web"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_tasklist_csv() {
+        let output = concat!(
+            // Headers are localized, e.g. German
+            "\"Abbildname\",\"PID\",\"Sitzungsname\",\"Sitz.-Nr.\",\"Speichernutzung\"\r\n",
+            "\"dotnet.exe\",\"4242\",\"Console\",\"1\",\"54,321 K\"\r\n",
+            "\"My App, Inc.exe\",\"5150\",\"Console\",\"1\",\"1.024 K\"\r\n",
+            "\"svchost.exe\",\"1337\",\"Services\",\"0\",\"8 K\"\r\n",
+            // Cut off while the output was read
+            "\"dotnet.exe\",\"61",
+        );
+        assert_eq!(
+            parse_tasklist(output),
+            [
+                process(4242, "dotnet.exe"),
+                DotnetProcess {
+                    pid: 5150,
+                    name: "My App, Inc.exe".to_string(),
+                    command_line: "My App, Inc.exe".to_string(),
+                },
+                process(1337, "svchost.exe"),
+            ]
+        );
+        assert!(
+            parse_tasklist("INFO: No tasks are running which match the specified criteria.")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn recognizes_dotnet_processes() {
+        let cases = [
+            (
+                "/usr/share/dotnet/dotnet /src/App/bin/Debug/net8.0/App.dll",
+                true,
+            ),
+            ("dotnet App.dll", true),
+            ("/src/App/bin/Debug/net8.0/App", true),
+            (r"C:\src\App\bin\Release\net8.0-windows\App.exe", true),
+            ("/usr/bin/mono-like-host --run Plugin.dll", true),
+            ("dotnet watch run", false),
+            ("dotnet build", false),
+            (
+                "/usr/share/dotnet/dotnet /usr/share/dotnet/sdk/8.0.100/MSBuild.dll",
+                false,
+            ),
+            ("/usr/bin/python3 server.py", false),
+            ("/src/App/obj/Debug/net8.0/App", false),
+        ];
+        for (command_line, expected) in cases {
+            assert_eq!(
+                is_dotnet_process(&process(1, command_line)),
+                expected,
+                "{command_line}"
+            );
+        }
+    }
+
+    #[test]
+    fn narrows_processes_to_a_directory() {
+        let processes = [
+            process(1, "dotnet /home/me/src/App/bin/Debug/net8.0/App.dll"),
+            process(2, "dotnet /home/me/src-other/App/bin/Debug/net8.0/App.dll"),
+            process(3, r"C:\Src\App\bin\Debug\net8.0\App.exe"),
+            process(
+                4,
+                "/home/me/src/Web/bin/Debug/net8.0/Web --urls http://+:5001",
+            ),
+        ];
+        let pids = |dir: &str| -> Vec<u32> {
+            running_from(&processes, dir)
+                .iter()
+                .map(|process| process.pid)
+                .collect()
+        };
+        assert_eq!(pids("/home/me/src"), [1, 4]);
+        assert_eq!(pids("/home/me/src/"), [1, 4]);
+        assert_eq!(pids("/home/me/SRC"), Vec::<u32>::new());
+        assert_eq!(pids(r"c:\src"), [3]);
+        assert_eq!(pids("/C:/src/App"), [3]);
+        assert_eq!(pids("/"), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn parses_elapsed_times_and_names() {
+        assert_eq!(parse_etime("05"), Some(5));
+        assert_eq!(parse_etime("01:05"), Some(65));
+        assert_eq!(
+            parse_etime("2-03:04:05"),
+            Some(2 * 86400 + 3 * 3600 + 4 * 60 + 5)
+        );
+        assert_eq!(parse_etime("ELAPSED"), None);
+
+        assert_eq!(stem(r"C:\bin\App.EXE"), "App");
+        assert_eq!(stem("/bin/App.dll"), "App");
+        assert_eq!(stem("App.Web"), "App.Web");
+        assert_eq!(
+            WaitingProcess::of(&process(
+                1,
+                "/usr/share/dotnet/dotnet exec /sdk/testhost.dll --port 1"
+            )),
+            Some(WaitingProcess::TestHost)
+        );
+        assert_eq!(
+            WaitingProcess::of(&process(2, "dotnet Worker.dll --functions-worker-id 7")),
+            Some(WaitingProcess::FunctionsWorker)
+        );
+        assert_eq!(WaitingProcess::of(&process(3, "dotnet App.dll")), None);
+    }
+}
@@ -0,0 +1,1111 @@
+use crate::dotnet::Dotnet;
+use crate::logger::{Logger, ScopedLogger};
+use crate::platform;
+use crate::version::compare_versions;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use zed_extension_api::serde_json::{self, Value};
+use zed_extension_api::{self as zed};
+
+const LOG: ScopedLogger = Logger::scoped("project");
+
+/// Placeholder `program` value asking the extension to locate the build output itself
+pub const AUTO_PROGRAM: &str = "${auto}";
+
+/// SDK of ASP.NET Core projects
+const WEB_SDK: &str = "Microsoft.NET.Sdk.Web";
+
+/// SDK of Blazor WebAssembly client projects
+const BLAZOR_WEBASSEMBLY_SDK: &str = "Microsoft.NET.Sdk.BlazorWebAssembly";
+
+/// Package every Azure Functions project in the isolated worker model references
+const FUNCTIONS_WORKER_PACKAGE: &str = "Microsoft.Azure.Functions.Worker";
+
+/// Packages only test projects reference
+const TEST_PACKAGES: [&str; 5] = [
+    "Microsoft.NET.Test.Sdk",
+    "xunit",
+    "NUnit",
+    "MSTest.TestFramework",
+    "MSTest",
+];
+
+/// Configuration built when neither the debug configuration nor the project sets one
+const DEFAULT_CONFIGURATION: &str = "Debug";
+
+/// How many directory levels below the search root are scanned for a project file
+const MAX_SEARCH_DEPTH: usize = 4;
+
+/// Directories that never contain the project being debugged
+const SKIPPED_DIRS: [&str; 4] = ["bin", "obj", "node_modules", "packages"];
+
+/// File in the work directory recording the project chosen for each worktree
+const CHOICES_FILE: &str = "project_choices.json";
+
+/// Number of `dotnet build` output lines shown when a build fails without a compiler error
+const BUILD_ERROR_TAIL_LINES: usize = 10;
+
+/// Whether the project is built before a launch session starts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BuildMode {
+    #[default]
+    Never,
+    Always,
+    /// Build only when a file in the project directory is newer than the output assembly
+    Auto,
+}
+
+impl Serialize for BuildMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Never => serializer.serialize_bool(false),
+            Self::Always => serializer.serialize_bool(true),
+            Self::Auto => serializer.serialize_str("auto"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BuildMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Bool(false) => Ok(Self::Never),
+            Value::Bool(true) => Ok(Self::Always),
+            Value::String(value) if value == "auto" => Ok(Self::Auto),
+            other => Err(serde::de::Error::custom(format!(
+                "invalid value {}, expected true, false or \"auto\"",
+                other
+            ))),
+        }
+    }
+}
+
+/// Whether the apphost executable built next to the project's assembly is launched instead of
+/// the assembly
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ApphostMode {
+    Never,
+    /// Fail when the project has no apphost
+    Always,
+    /// Use the apphost when it exists
+    #[default]
+    Auto,
+}
+
+impl Serialize for ApphostMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Never => serializer.serialize_bool(false),
+            Self::Always => serializer.serialize_bool(true),
+            Self::Auto => serializer.serialize_str("auto"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ApphostMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Bool(false) => Ok(Self::Never),
+            Value::Bool(true) => Ok(Self::Always),
+            Value::String(value) if value == "auto" => Ok(Self::Auto),
+            other => Err(serde::de::Error::custom(format!(
+                "invalid value {}, expected true, false or \"auto\"",
+                other
+            ))),
+        }
+    }
+}
+
+/// How a launch configuration without `program` starts the project's build output
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LaunchMode {
+    /// The output assembly, through the `dotnet` host
+    #[default]
+    Dll,
+    /// The command `dotnet run` starts, as evaluated by MSBuild from `RunCommand` and
+    /// `RunArguments`
+    DotnetRun,
+}
+
+/// The MSBuild properties `dotnet run` starts the project with
+#[derive(Deserialize)]
+struct RunProperties {
+    #[serde(rename = "RunCommand", default)]
+    run_command: String,
+    #[serde(rename = "RunArguments", default)]
+    run_arguments: String,
+}
+
+#[derive(Deserialize)]
+struct Evaluation {
+    #[serde(rename = "Properties")]
+    properties: RunProperties,
+}
+
+/// The properties of a `.csproj` file needed to locate the assembly it builds
+pub struct Project {
+    pub path: PathBuf,
+    assembly_name: String,
+    target_frameworks: Vec<String>,
+    output_path: Option<String>,
+    configuration: Option<String>,
+    /// Configurations declared with `Configurations`, empty for the SDK's Debug and Release
+    configurations: Vec<String>,
+    append_target_framework: bool,
+}
+
+impl Project {
+    /// Finds the project to debug. If `explicit`, `path` was configured as the project and
+    /// names a `.csproj` file or a directory containing one. Otherwise the shallowest
+    /// executable project below `path` is used, skipping class libraries; several candidates
+    /// at that depth are ambiguous unless one of them is the `remembered` choice.
+    pub fn find(path: &Path, explicit: bool, remembered: Option<&Path>) -> Result<PathBuf, String> {
+        if explicit {
+            let found = if path.is_file() {
+                path.to_path_buf()
+            } else if !path.is_dir() {
+                return Err(format!("Project '{}' does not exist", path.display()));
+            } else {
+                match Self::projects_in(path).as_slice() {
+                    [found] => found.clone(),
+                    [] => return Err(format!("No .csproj file found in '{}'", path.display())),
+                    found => return Err(Self::ambiguous(found, path)),
+                }
+            };
+            if Self::is_blazor_client(&found) {
+                return Err(Self::blazor_client_error(&found));
+            }
+            return Ok(found);
+        }
+
+        let mut level = vec![path.to_path_buf()];
+        let mut found_tests = false;
+        let mut blazor_clients = Vec::new();
+        for _ in 0..=MAX_SEARCH_DEPTH {
+            // Blazor WebAssembly clients run in the browser, even if they declare an OutputType
+            let (clients, projects): (Vec<PathBuf>, Vec<PathBuf>) = level
+                .iter()
+                .flat_map(|dir| Self::projects_in(dir))
+                .partition(|project| Self::is_blazor_client(project));
+            blazor_clients.extend(clients);
+            let (found, libraries): (Vec<PathBuf>, Vec<PathBuf>) = projects
+                .into_iter()
+                .partition(|project| Self::is_executable(project));
+            found_tests |= libraries
+                .iter()
+                .any(|project| Self::is_test_project(project));
+            match found.as_slice() {
+                [found] => {
+                    if !blazor_clients.is_empty() {
+                        LOG.info(&format!(
+                            "Using {}{}, as Blazor WebAssembly clients run in the browser where netcoredbg cannot debug them: skipped {}",
+                            found.display(),
+                            if Self::hosts_any(found, &blazor_clients) {
+                                ", which hosts the client"
+                            } else {
+                                ""
+                            },
+                            blazor_clients
+                                .iter()
+                                .map(|client| client.display().to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    }
+                    return Ok(found.clone());
+                }
+                [] => {}
+                found => {
+                    return match remembered
+                        .filter(|remembered| found.iter().any(|f| f == remembered))
+                    {
+                        Some(remembered) => {
+                            LOG.info(&format!(
+                                "Using the previously chosen project {}",
+                                remembered.display()
+                            ));
+                            Ok(remembered.to_path_buf())
+                        }
+                        None => Err(Self::ambiguous(found, path)),
+                    }
+                }
+            }
+            level = level.iter().flat_map(|dir| Self::subdirs(dir)).collect();
+        }
+        if let [client, ..] = blazor_clients.as_slice() {
+            return Err(Self::blazor_client_error(client));
+        }
+        if found_tests {
+            return Err(format!(
+                "'{}' contains no executable project, only test projects, which cannot be launched. Run `dotnet test` with VSTEST_HOST_DEBUG=1 set and attach to the test host instead.",
+                path.display()
+            ));
+        }
+        Err(format!(
+            "No executable .csproj file found in '{}'. Set 'program' to the assembly to debug, or 'project' to the project that builds it.",
+            path.display()
+        ))
+    }
+
+    /// The project last configured with `project` for the worktree at `worktree_root`
+    pub fn remembered(worktree_root: &str) -> Option<PathBuf> {
+        let choices = std::fs::read_to_string(CHOICES_FILE).ok()?;
+        let choices: HashMap<String, PathBuf> = serde_json::from_str(&choices).ok()?;
+        choices.get(worktree_root).cloned()
+    }
+
+    /// Remembers `project` as the one to debug in the worktree at `worktree_root` when the
+    /// choice between several projects is ambiguous
+    pub fn remember(worktree_root: &str, project: &Path) {
+        let mut choices: HashMap<String, PathBuf> = std::fs::read_to_string(CHOICES_FILE)
+            .ok()
+            .and_then(|choices| serde_json::from_str(&choices).ok())
+            .unwrap_or_default();
+        if choices.get(worktree_root).map(PathBuf::as_path) == Some(project) {
+            return;
+        }
+        choices.insert(worktree_root.to_string(), project.to_path_buf());
+        let result = serde_json::to_string_pretty(&choices)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(CHOICES_FILE, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            LOG.warn(&format!("Failed to remember the chosen project: {}", e));
+        }
+    }
+
+    /// The nearest directory above `assembly` that contains a project file, which for build
+    /// output in `bin/<configuration>/<framework>` is the directory of the project that built it
+    pub fn dir_containing(assembly: &Path) -> Option<PathBuf> {
+        assembly
+            .ancestors()
+            .skip(1)
+            .find(|dir| !Self::projects_in(dir).is_empty())
+            .map(Path::to_path_buf)
+    }
+
+    /// Reads the project file at `path`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read project '{}': {}", path.display(), e))?;
+        let content = strip_comments(&content);
+        let project_name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let target_frameworks: Vec<String> = property(&content, "TargetFrameworks")
+            .or_else(|| property(&content, "TargetFramework"))
+            .map(|frameworks| {
+                frameworks
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|framework| !framework.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if target_frameworks.is_empty() {
+            return Err(format!(
+                "Project '{}' does not declare a TargetFramework. Set 'program' to the assembly to debug.",
+                path.display()
+            ));
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            assembly_name: property(&content, "AssemblyName")
+                .map(|name| name.replace("$(MSBuildProjectName)", &project_name))
+                .unwrap_or(project_name),
+            target_frameworks,
+            output_path: property(&content, "OutputPath"),
+            configuration: property(&content, "Configuration"),
+            configurations: property(&content, "Configurations")
+                .map(|configurations| {
+                    configurations
+                        .split(';')
+                        .map(str::trim)
+                        .filter(|configuration| !configuration.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            append_target_framework: property(&content, "AppendTargetFrameworkToOutputPath")
+                .is_none_or(|value| !value.eq_ignore_ascii_case("false")),
+        })
+    }
+
+    /// Path of the assembly built for `configuration` (see [`Self::configuration`]) and
+    /// `target_framework`, by default the newest targeted framework with a build on disk, or
+    /// else the newest targeted framework
+    pub fn output_assembly(
+        &self,
+        configuration: Option<&str>,
+        target_framework: Option<&str>,
+    ) -> Result<PathBuf, String> {
+        let configuration = self.configuration(configuration);
+        if !self.configurations.is_empty()
+            && !self
+                .configurations
+                .iter()
+                .any(|declared| declared.eq_ignore_ascii_case(configuration))
+        {
+            return Err(format!(
+                "Project '{}' has no '{}' configuration. Configurations: {}",
+                self.path.display(),
+                configuration,
+                self.configurations.join(", ")
+            ));
+        }
+
+        let framework = match target_framework {
+            Some(framework) => self
+                .target_frameworks
+                .iter()
+                .find(|targeted| targeted.eq_ignore_ascii_case(framework))
+                .ok_or_else(|| {
+                    format!(
+                        "Project '{}' does not target '{}'. Targeted frameworks: {}",
+                        self.path.display(),
+                        framework,
+                        self.target_frameworks.join(", ")
+                    )
+                })?,
+            None => {
+                let built = self.built_frameworks(configuration);
+                let candidates = if built.is_empty() {
+                    self.target_frameworks.iter().map(String::as_str).collect()
+                } else {
+                    built
+                };
+                candidates
+                    .into_iter()
+                    .max_by(|a, b| compare_frameworks(a, b))
+                    .expect("projects target at least one framework")
+            }
+        };
+        Ok(self.assembly_path(configuration, framework))
+    }
+
+    /// The apphost executable built next to `assembly` (`App.exe` for `App.dll` on Windows,
+    /// `App` elsewhere), if the build produced one. Paths that are not `.dll` assemblies, such
+    /// as an executable or `App.Web` without extension, have none.
+    pub fn apphost(assembly: &Path) -> Option<PathBuf> {
+        let is_assembly = assembly
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("dll"));
+        if !is_assembly {
+            return None;
+        }
+        let apphost = match platform::os() {
+            zed::Os::Windows => assembly.with_extension("exe"),
+            zed::Os::Mac | zed::Os::Linux => assembly.with_extension(""),
+        };
+        apphost.is_file().then_some(apphost)
+    }
+
+    /// The program to launch for the built `assembly` as `mode` asks: its apphost if one was
+    /// built next to it, or else the assembly itself
+    pub fn launch_target(assembly: &Path, mode: ApphostMode) -> Result<PathBuf, String> {
+        match (mode, Self::apphost(assembly)) {
+            (ApphostMode::Never, _) if !assembly.is_file() => Err(format!(
+                "'{}' does not exist, only its apphost. Set 'useApphost' to \"auto\" to launch the apphost.",
+                assembly.display()
+            )),
+            (ApphostMode::Never, _) => Ok(assembly.to_path_buf()),
+            // Launching the apphost keeps `MainModule` and apphost-relative probing working
+            (ApphostMode::Always | ApphostMode::Auto, Some(apphost)) => Ok(apphost),
+            (ApphostMode::Always, None) => Err(format!(
+                "The project builds no apphost next to '{}'. Set 'useApphost' to \"auto\" or false to launch the assembly, or enable UseAppHost in the project.",
+                assembly.display()
+            )),
+            (ApphostMode::Auto, None) => Ok(assembly.to_path_buf()),
+        }
+    }
+
+    /// The targeted frameworks whose assembly has been built for `configuration`
+    pub fn built_frameworks(&self, configuration: &str) -> Vec<&str> {
+        self.target_frameworks
+            .iter()
+            .map(String::as_str)
+            .filter(|framework| self.assembly_path(configuration, framework).is_file())
+            .collect()
+    }
+
+    /// The configuration to build: `configuration` if set, else the project's, else Debug, or
+    /// the first of the project's `Configurations` if it does not declare Debug
+    pub fn configuration<'a>(&'a self, configuration: Option<&'a str>) -> &'a str {
+        configuration
+            .or(self.configuration.as_deref())
+            .unwrap_or_else(|| match self.configurations.first() {
+                Some(first)
+                    if !self
+                        .configurations
+                        .iter()
+                        .any(|declared| declared.eq_ignore_ascii_case(DEFAULT_CONFIGURATION)) =>
+                {
+                    first
+                }
+                _ => DEFAULT_CONFIGURATION,
+            })
+    }
+
+    fn assembly_path(&self, configuration: &str, framework: &str) -> PathBuf {
+        let project_dir = self.path.parent().unwrap_or(Path::new(""));
+        let mut output_dir = match &self.output_path {
+            Some(output_path) => project_dir.join(
+                output_path
+                    .replace("$(Configuration)", configuration)
+                    .replace("$(TargetFramework)", framework)
+                    .replace('\\', "/"),
+            ),
+            None => project_dir.join("bin").join(configuration),
+        };
+        let output_path_has_framework = self
+            .output_path
+            .as_ref()
+            .is_some_and(|output_path| output_path.contains("$(TargetFramework)"));
+        if self.append_target_framework && !output_path_has_framework {
+            output_dir.push(framework);
+        }
+        output_dir.join(format!("{}.dll", self.assembly_name))
+    }
+
+    /// Whether `assembly` exists and is newer than every file in the project directory,
+    /// ignoring build output and hidden directories
+    pub fn is_up_to_date(&self, assembly: &Path) -> bool {
+        fn newest_source(dir: &Path) -> Option<SystemTime> {
+            let files = std::fs::read_dir(dir)
+                .ok()?
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .filter_map(|path| {
+                    path.metadata()
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                });
+            let nested = Project::subdirs(dir)
+                .into_iter()
+                .filter_map(|subdir| newest_source(&subdir));
+            files.chain(nested).max()
+        }
+
+        let Ok(built) = assembly.metadata().and_then(|metadata| metadata.modified()) else {
+            return false;
+        };
+        let project_dir = self.path.parent().unwrap_or(Path::new(""));
+        newest_source(project_dir).is_none_or(|newest| newest <= built)
+    }
+
+    /// Runs `dotnet build` for `configuration` with the given `dotnet` host, logging its
+    /// output. A failed build is
+    /// reported with its first compiler error, or the last output lines if there is none.
+    pub fn build(
+        &self,
+        dotnet: &Dotnet,
+        configuration: &str,
+        env: &[(String, String)],
+    ) -> Result<(), String> {
+        let project = self.path.to_string_lossy();
+        LOG.info(&format!(
+            "Running dotnet build {} -c {}",
+            project, configuration
+        ));
+        let output = dotnet
+            .command(env)
+            .args(["build", &project, "-c", configuration])
+            .output()
+            .map_err(|e| format!("Failed to run dotnet build for '{}': {}", project, e))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let lines: Vec<&str> = stdout
+            .lines()
+            .chain(stderr.lines())
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        for line in &lines {
+            LOG.debug(&format!("dotnet build: {}", line));
+        }
+        if output.status == Some(0) {
+            LOG.info(&format!("Built {}", project));
+            return Ok(());
+        }
+
+        let snippet = match lines.iter().find(|line| line.contains(": error ")) {
+            Some(error) => error.trim().to_string(),
+            None => lines[lines.len().saturating_sub(BUILD_ERROR_TAIL_LINES)..].join("\n"),
+        };
+        Err(format!(
+            "dotnet build failed for '{}' (exit status {:?}):\n{}",
+            project, output.status, snippet
+        ))
+    }
+
+    /// The program and arguments `dotnet run` would start for the build of `assembly`, asking
+    /// MSBuild (`dotnet msbuild -getProperty`, .NET 8 SDK and later) so that custom run targets
+    /// and workload hosts are honored. netcoredbg does not follow child processes, so the
+    /// command is launched directly rather than through `dotnet run`. A `dotnet exec App.dll`
+    /// command becomes a launch of `App.dll`.
+    pub fn run_command(
+        &self,
+        dotnet: &Dotnet,
+        configuration: &str,
+        assembly: &Path,
+        env: &[(String, String)],
+    ) -> Result<(String, Vec<String>), String> {
+        let project = self.path.to_string_lossy();
+        let mut args = vec![
+            "msbuild".to_string(),
+            project.to_string(),
+            "-getProperty:RunCommand".to_string(),
+            "-getProperty:RunArguments".to_string(),
+            format!("-p:Configuration={}", configuration),
+        ];
+        let framework = assembly
+            .parent()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy())
+            .filter(|name| {
+                self.target_frameworks
+                    .iter()
+                    .any(|framework| framework.eq_ignore_ascii_case(name))
+            });
+        if let Some(framework) = framework {
+            args.push(format!("-p:TargetFramework={}", framework));
+        }
+        let output = dotnet
+            .command(env)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run dotnet msbuild for '{}': {}", project, e))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let evaluation = serde_json::from_str::<Evaluation>(&stdout)
+            .ok()
+            .filter(|_| output.status == Some(0))
+            .ok_or_else(|| {
+                format!(
+                    "Failed to evaluate the run command of '{}'; 'launchMode': 'dotnetRun' needs the .NET 8 SDK or later. Use 'launchMode': 'dll' with older SDKs. dotnet msbuild: {}{}",
+                    project,
+                    stdout.trim(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )
+            })?;
+        let RunProperties {
+            run_command,
+            run_arguments,
+        } = evaluation.properties;
+        if run_command.is_empty() {
+            return Err(format!(
+                "Project '{}' has no run command, so `dotnet run` cannot start it",
+                project
+            ));
+        }
+        LOG.info(&format!(
+            "dotnet run would start: {} {}",
+            run_command, run_arguments
+        ));
+
+        let args = crate::args::split_command_line(&run_arguments)?;
+        let host = run_command.rsplit(['/', '\\']).next().unwrap_or_default();
+        if host.eq_ignore_ascii_case("dotnet") || host.eq_ignore_ascii_case("dotnet.exe") {
+            if let Some(index) = args
+                .iter()
+                .position(|arg| arg.to_ascii_lowercase().ends_with(".dll"))
+            {
+                return Ok((args[index].clone(), args[index + 1..].to_vec()));
+            }
+        }
+        Ok((run_command, args))
+    }
+
+    fn projects_in(dir: &Path) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut projects: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .is_some_and(|extension| extension.eq_ignore_ascii_case("csproj"))
+            })
+            .collect();
+        projects.sort();
+        projects
+    }
+
+    fn subdirs(dir: &Path) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut dirs: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                path.is_dir()
+                    && !name.starts_with('.')
+                    && !SKIPPED_DIRS
+                        .iter()
+                        .any(|skipped| name.eq_ignore_ascii_case(skipped))
+            })
+            .collect();
+        dirs.sort();
+        dirs
+    }
+
+    /// Whether the project builds an application rather than a class library
+    fn is_executable(path: &Path) -> bool {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return false;
+        };
+        let content = strip_comments(&content);
+        let is_app_sdk = [WEB_SDK, "Microsoft.NET.Sdk.Worker"]
+            .iter()
+            .any(|sdk| content.contains(&format!("Sdk=\"{}\"", sdk)));
+        is_app_sdk
+            || property(&content, "OutputType").is_some_and(|output_type| {
+                output_type.eq_ignore_ascii_case("Exe")
+                    || output_type.eq_ignore_ascii_case("WinExe")
+            })
+    }
+
+    /// Whether the project uses the ASP.NET Core (Web) SDK
+    pub fn is_web(path: &Path) -> bool {
+        std::fs::read_to_string(path)
+            .is_ok_and(|content| strip_comments(&content).contains(&format!("Sdk=\"{}\"", WEB_SDK)))
+    }
+
+    /// Whether the project is an Azure Functions app in the isolated worker model, whose
+    /// assembly only runs as a worker of the Functions host
+    pub fn is_functions(path: &Path) -> bool {
+        std::fs::read_to_string(path).is_ok_and(|content| {
+            strip_comments(&content).contains(&format!("Include=\"{}\"", FUNCTIONS_WORKER_PACKAGE))
+        })
+    }
+
+    /// The `UserSecretsId` the project's user secrets are stored under
+    pub fn user_secrets_id(path: &Path) -> Option<String> {
+        let content = std::fs::read_to_string(path).ok()?;
+        property(&strip_comments(&content), "UserSecretsId")
+    }
+
+    /// Whether the project is a Blazor WebAssembly client, whose code runs in the browser
+    fn is_blazor_client(path: &Path) -> bool {
+        std::fs::read_to_string(path).is_ok_and(|content| {
+            strip_comments(&content).contains(&format!("Sdk=\"{}\"", BLAZOR_WEBASSEMBLY_SDK))
+        })
+    }
+
+    /// Whether the project references one of the Blazor WebAssembly `clients`, i.e. is the
+    /// server of a hosted Blazor app
+    fn hosts_any(path: &Path, clients: &[PathBuf]) -> bool {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return false;
+        };
+        let content = strip_comments(&content);
+        clients
+            .iter()
+            .filter_map(|client| client.file_name())
+            .any(|name| {
+                content
+                    .lines()
+                    .filter(|line| line.contains("<ProjectReference"))
+                    .any(|line| line.contains(&*name.to_string_lossy()))
+            })
+    }
+
+    /// The error for a Blazor WebAssembly client chosen as the project to launch, naming the
+    /// server project hosting it next to it if there is one
+    fn blazor_client_error(client: &Path) -> String {
+        let server = client
+            .parent()
+            .and_then(Path::parent)
+            .map(Self::subdirs)
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|dir| Self::projects_in(dir))
+            .find(|project| Self::hosts_any(project, &[client.to_path_buf()]));
+        let hint = match server {
+            Some(server) => format!(
+                "Debug the server project hosting it instead by setting 'project' to '{}'",
+                server.display()
+            ),
+            None => "Debug the server side of the app instead".to_string(),
+        };
+        format!(
+            "'{}' is a Blazor WebAssembly client, whose code runs in the browser, where netcoredbg cannot debug it. {}, and use the browser's developer tools for the client code.",
+            client.display(),
+            hint
+        )
+    }
+
+    /// Whether the project holds tests run by `dotnet test`: it sets `IsTestProject` or
+    /// references the test SDK or a test framework
+    fn is_test_project(path: &Path) -> bool {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return false;
+        };
+        let content = strip_comments(&content);
+        property(&content, "IsTestProject").is_some_and(|value| value.eq_ignore_ascii_case("true"))
+            || TEST_PACKAGES
+                .iter()
+                .any(|package| content.contains(&format!("Include=\"{}\"", package)))
+    }
+
+    fn ambiguous(found: &[PathBuf], root: &Path) -> String {
+        format!(
+            "Found several projects: {}. Set 'project' to the one to debug; it is remembered for this worktree.",
+            found
+                .iter()
+                .map(|path| path.strip_prefix(root).unwrap_or(path).display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Orders target framework monikers so that the newest runnable framework sorts last:
+/// `netstandard*` < .NET Framework (`net48`) < `netcoreapp*` < `net5.0` and later.
+/// Platform suffixes such as `-windows` are ignored.
+fn compare_frameworks(a: &str, b: &str) -> Ordering {
+    fn rank(framework: &str) -> (u8, &str) {
+        let framework = framework.split('-').next().unwrap_or(framework);
+        if let Some(version) = framework.strip_prefix("netstandard") {
+            (0, version)
+        } else if let Some(version) = framework.strip_prefix("netcoreapp") {
+            (2, version)
+        } else if let Some(version) = framework.strip_prefix("net") {
+            (if version.contains('.') { 3 } else { 1 }, version)
+        } else {
+            (0, framework)
+        }
+    }
+
+    let (a_family, a_version) = rank(a);
+    let (b_family, b_version) = rank(b);
+    a_family
+        .cmp(&b_family)
+        .then_with(|| compare_versions(a_version, b_version))
+}
+
+/// Returns the text of the first `<name>` element, preferring one without a `Condition` on
+/// it or its `PropertyGroup` so that configuration-specific overrides do not shadow the
+/// project-wide value
+fn property(content: &str, name: &str) -> Option<String> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut conditional = None;
+    let mut rest = content;
+
+    while let Some(start) = rest.find(&open) {
+        let group = &content[..content.len() - rest.len() + start];
+        let in_conditional_group = group
+            .rfind("<PropertyGroup")
+            .filter(|&index| !group[index..].contains("</PropertyGroup>"))
+            .and_then(|index| {
+                let tag = &group[index..];
+                tag.find('>').map(|end| tag[..end].contains("Condition"))
+            })
+            .unwrap_or(false);
+        let after_name = &rest[start + open.len()..];
+        rest = after_name;
+        let Some(tag_end) = after_name.find('>') else {
+            break;
+        };
+        let attributes = &after_name[..tag_end];
+        if !(attributes.is_empty() || attributes.starts_with(char::is_whitespace))
+            || attributes.ends_with('/')
+        {
+            continue;
+        }
+        let body = &after_name[tag_end + 1..];
+        let Some(body_end) = body.find(&close) else {
+            break;
+        };
+        let value = body[..body_end].trim().to_string();
+        if value.is_empty() {
+            continue;
+        }
+        if !attributes.contains("Condition") && !in_conditional_group {
+            return Some(value);
+        }
+        conditional.get_or_insert(value);
+    }
+    conditional
+}
+
+fn strip_comments(content: &str) -> String {
+    let mut stripped = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("<!--") {
+        stripped.push_str(&rest[..start]);
+        rest = match rest[start..].find("-->") {
+            Some(end) => &rest[start + end + 3..],
+            None => "",
+        };
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    const MULTI_TARGETED: &str = r#"<Project Sdk="Microsoft.NET.Sdk">
+  <PropertyGroup>
+    <OutputType>Exe</OutputType>
+    <!-- <TargetFramework>net472</TargetFramework> -->
+    <TargetFrameworks>net6.0; net8.0 ;netstandard2.0</TargetFrameworks>
+    <Configurations>Debug;Release;Staging</Configurations>
+  </PropertyGroup>
+</Project>
+"#;
+
+    #[test]
+    fn reads_properties_preferring_unconditional_values() {
+        let content = r#"
+  <PropertyGroup Condition="'$(Configuration)' == 'Release'">
+    <OutputPath>bin/Shipping</OutputPath>
+  </PropertyGroup>
+  <PropertyGroup>
+    <AssemblyName />
+    <TargetFrameworks>net8.0</TargetFrameworks>
+    <TargetFramework>net6.0</TargetFramework>
+    <OutputPath>out</OutputPath>
+  </PropertyGroup>"#;
+        assert_eq!(property(content, "OutputPath").as_deref(), Some("out"));
+        // `<TargetFramework` must not match the start of `<TargetFrameworks>`
+        assert_eq!(
+            property(content, "TargetFramework").as_deref(),
+            Some("net6.0")
+        );
+        assert_eq!(
+            property(content, "TargetFrameworks").as_deref(),
+            Some("net8.0")
+        );
+        assert_eq!(property(content, "AssemblyName"), None);
+        assert_eq!(
+            property(
+                r#"<OutputPath Condition="'$(Configuration)' == 'Debug'">dbg</OutputPath>"#,
+                "OutputPath"
+            )
+            .as_deref(),
+            Some("dbg")
+        );
+        assert_eq!(
+            property(
+                r#"<PropertyGroup Condition="'$(Configuration)' == 'Release'"><Optimize>true</Optimize></PropertyGroup>"#,
+                "Optimize"
+            )
+            .as_deref(),
+            Some("true")
+        );
+        assert_eq!(
+            strip_comments("a<!-- <b>ignored</b> -->c<!-- unterminated"),
+            "ac"
+        );
+    }
+
+    #[test]
+    fn orders_target_frameworks() {
+        let mut frameworks = [
+            "net8.0",
+            "netcoreapp3.1",
+            "net48",
+            "net10.0",
+            "netstandard2.1",
+            "net6.0-windows",
+        ];
+        frameworks.sort_by(|a, b| compare_frameworks(a, b));
+        assert_eq!(
+            frameworks,
+            [
+                "netstandard2.1",
+                "net48",
+                "netcoreapp3.1",
+                "net6.0-windows",
+                "net8.0",
+                "net10.0"
+            ]
+        );
+    }
+
+    #[test]
+    fn loads_single_and_multi_targeted_projects() {
+        let dir = TempDir::new();
+        let single = Project::load(&dir.write(
+            "Single/Single.csproj",
+            "<Project><PropertyGroup><TargetFramework>net8.0</TargetFramework>\
+             <AssemblyName>$(MSBuildProjectName).Host</AssemblyName></PropertyGroup></Project>",
+        ))
+        .unwrap();
+        assert_eq!(single.target_frameworks, ["net8.0"]);
+        assert_eq!(single.assembly_name, "Single.Host");
+        assert_eq!(single.configuration(None), "Debug");
+
+        let multi = Project::load(&dir.write("App/App.csproj", MULTI_TARGETED)).unwrap();
+        assert_eq!(
+            multi.target_frameworks,
+            ["net6.0", "net8.0", "netstandard2.0"]
+        );
+        assert_eq!(multi.configurations, ["Debug", "Release", "Staging"]);
+
+        let error = match Project::load(&dir.write("Lib/Lib.csproj", "<Project />")) {
+            Ok(_) => panic!("a project without a TargetFramework loads"),
+            Err(error) => error,
+        };
+        assert!(
+            error.contains("does not declare a TargetFramework"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn picks_the_newest_built_target_framework() {
+        let dir = TempDir::new();
+        let project = Project::load(&dir.write("App/App.csproj", MULTI_TARGETED)).unwrap();
+        let output = |framework: &str| {
+            dir.path()
+                .join(format!("App/bin/Debug/{framework}/App.dll"))
+        };
+
+        // Nothing is built yet: the newest targeted framework
+        assert_eq!(
+            project.output_assembly(None, None).unwrap(),
+            output("net8.0")
+        );
+
+        dir.write("App/bin/Debug/net6.0/App.dll", "");
+        assert_eq!(project.built_frameworks("Debug"), ["net6.0"]);
+        assert_eq!(
+            project.output_assembly(None, None).unwrap(),
+            output("net6.0")
+        );
+        assert_eq!(
+            project.output_assembly(None, Some("NET8.0")).unwrap(),
+            output("net8.0")
+        );
+
+        dir.write("App/bin/Debug/net8.0/App.dll", "");
+        assert_eq!(
+            project.output_assembly(None, None).unwrap(),
+            output("net8.0")
+        );
+        assert_eq!(
+            project.output_assembly(Some("Staging"), None).unwrap(),
+            dir.path().join("App/bin/Staging/net8.0/App.dll")
+        );
+
+        let error = project.output_assembly(None, Some("net7.0")).unwrap_err();
+        assert!(
+            error.contains("Targeted frameworks: net6.0, net8.0, netstandard2.0"),
+            "{error}"
+        );
+        let error = project.output_assembly(Some("Test"), None).unwrap_err();
+        assert!(
+            error.contains("Configurations: Debug, Release, Staging"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn composes_custom_output_paths() {
+        let dir = TempDir::new();
+        let project = Project::load(&dir.write(
+            "App/App.csproj",
+            r"<Project><PropertyGroup>
+                <TargetFramework>net8.0</TargetFramework>
+                <Configurations>Release;Profiling</Configurations>
+                <OutputPath>..\artifacts\$(Configuration)\</OutputPath>
+              </PropertyGroup></Project>",
+        ))
+        .unwrap();
+        // Without a Debug configuration the first declared one is the default
+        assert_eq!(project.configuration(None), "Release");
+        assert_eq!(
+            project.output_assembly(None, None).unwrap(),
+            dir.path().join("App/../artifacts/Release/net8.0/App.dll")
+        );
+
+        let flat = Project::load(&dir.write(
+            "Flat/Flat.csproj",
+            "<Project><PropertyGroup><TargetFramework>net8.0</TargetFramework>\
+             <AppendTargetFrameworkToOutputPath>false</AppendTargetFrameworkToOutputPath>\
+             </PropertyGroup></Project>",
+        ))
+        .unwrap();
+        assert_eq!(
+            flat.output_assembly(Some("Release"), None).unwrap(),
+            dir.path().join("Flat/bin/Release/Flat.dll")
+        );
+    }
+
+    #[test]
+    fn launches_the_apphost_next_to_built_assemblies() {
+        let dir = TempDir::new();
+        let both = dir.write("both/App.dll", "");
+        dir.write("both/App", "");
+        let dll_only = dir.write("dll/App.dll", "");
+        let apphost_only = dir.path().join("apphost/App.dll");
+        dir.write("apphost/App", "");
+
+        assert_eq!(Project::apphost(&both), Some(dir.path().join("both/App")));
+        assert_eq!(Project::apphost(&dll_only), None);
+        assert_eq!(
+            Project::launch_target(&both, ApphostMode::Auto).unwrap(),
+            dir.path().join("both/App")
+        );
+        assert_eq!(
+            Project::launch_target(&both, ApphostMode::Never).unwrap(),
+            both
+        );
+        assert_eq!(
+            Project::launch_target(&dll_only, ApphostMode::Auto).unwrap(),
+            dll_only
+        );
+        assert!(Project::launch_target(&dll_only, ApphostMode::Always).is_err());
+        assert_eq!(
+            Project::launch_target(&apphost_only, ApphostMode::Auto).unwrap(),
+            dir.path().join("apphost/App")
+        );
+        assert!(Project::launch_target(&apphost_only, ApphostMode::Never).is_err());
+    }
+
+    #[test]
+    fn keeps_programs_that_are_not_assemblies() {
+        let dir = TempDir::new();
+        // A self-contained single-file executable and a dotted project name next to files that
+        // would be mistaken for their apphost if the last extension were swapped away
+        let single_file = dir.write("publish/App", "");
+        let dotted = dir.write("web/App.Web", "");
+        dir.write("web/App", "");
+
+        for program in [&single_file, &dotted] {
+            assert_eq!(
+                Project::apphost(program),
+                None,
+                "program: {}",
+                program.display()
+            );
+            assert_eq!(
+                Project::launch_target(program, ApphostMode::Auto).unwrap(),
+                *program
+            );
+        }
+        let upper = dir.write("upper/App.DLL", "");
+        dir.write("upper/App", "");
+        assert_eq!(Project::apphost(&upper), Some(dir.path().join("upper/App")));
+    }
+}
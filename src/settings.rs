@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings scope a user-provided netcoredbg binary path came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathScope {
+    /// The `netcoredbg.binary` field of the project's debug configuration
+    DebugConfiguration,
+    /// `dap.netcoredbg.binary` in the Zed settings
+    ZedSettings,
+}
+
+impl std::fmt::Display for PathScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::DebugConfiguration => "'netcoredbg.binary' in the debug configuration",
+            Self::ZedSettings => "'dap.netcoredbg.binary' in the Zed settings",
+        })
+    }
+}
+
+/// When a newer netcoredbg release than the newest installed version is installed
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoUpdate {
+    /// Install newer releases as soon as they are found
+    #[default]
+    Always,
+    /// Keep the installed version and log that an update is available
+    Prompt,
+    /// Keep the installed version without checking for updates
+    Never,
+}
+
+/// Most verbose messages written to the extension's debug log
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    /// Parses a level name case-insensitively, e.g. from an environment variable
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "error" => Some(Self::Error),
+            "warn" | "warning" => Some(Self::Warn),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            _ => None,
+        }
+    }
+
+    /// Column written in front of each log line
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN ",
+            Self::Info => "INFO ",
+            Self::Debug => "DEBUG",
+        }
+    }
+}
+
+/// Line format of the extension's debug log
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Adapter settings read from the `netcoredbg` object of a debug configuration.
+/// These control how the netcoredbg binary is resolved and are not used by netcoredbg itself.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct AdapterSettings {
+    /// Path to a netcoredbg binary for this project, taking precedence over the Zed settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary: Option<String>,
+    /// Release tag to install instead of the latest release (e.g. "3.1.2-1054")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Expected SHA256 digest of the netcoredbg executable, verified after download
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// Whether reused installs are checked against a digest over all installed files instead of
+    /// only the executable (default false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify_full_install: Option<bool>,
+    /// Number of installed versions to keep when cleaning up after an upgrade (default 2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_versions: Option<usize>,
+    /// GitHub repository (`owner/repo`) to download releases from instead of the default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_repo: Option<String>,
+    /// GitHub token used to authenticate release lookups and avoid the unauthenticated rate
+    /// limit. Only taken from `GITHUB_TOKEN` in the worktree's shell environment, never from the
+    /// debug configuration.
+    #[serde(skip)]
+    pub github_token: Option<String>,
+    /// Direct URL of a `.zip`, `.tar.gz` or `.tar.xz` netcoredbg archive (or a bare executable),
+    /// bypassing GitHub entirely
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+    /// Number of attempts for release lookups and downloads that fail transiently (default 3)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_attempts: Option<u32>,
+    /// Delay before the first retry in milliseconds, doubled on each attempt (default 500)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_delay_ms: Option<u64>,
+    /// Seconds after which failing release lookups and downloads stop being retried (default 30)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// How long a cached latest-release lookup is reused before querying GitHub again (default 24)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_cache_ttl_hours: Option<u64>,
+    /// Whether a netcoredbg found on PATH is used before downloading one (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefer_system_binary: Option<bool>,
+    /// Whether pre-release builds of netcoredbg may be installed (default false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_prerelease: Option<bool>,
+    /// Exact release asset name to download, overriding platform and libc detection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_asset: Option<String>,
+    /// Whether a freshly installed binary is verified by running `netcoredbg --version` (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_check: Option<bool>,
+    /// Whether the resolved binary is started with `--version` before each session to report
+    /// startup failures with netcoredbg's stderr (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preflight_check: Option<bool>,
+    /// Directory netcoredbg versions are installed into; relative paths resolve against the
+    /// worktree root. Defaults to the extension's work directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install_dir: Option<String>,
+    /// Removes and re-downloads the installed netcoredbg once per Zed session, to repair a
+    /// corrupted install (default false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_reinstall: Option<bool>,
+    /// Whether every extracted file is installed instead of only the executable, libraries and
+    /// license files (default false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copy_all_files: Option<bool>,
+    /// Whether newer releases replace the installed version (default "always")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_update: Option<AutoUpdate>,
+    /// Most verbose messages written to the extension's debug log (default "warn")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<LogLevel>,
+    /// Line format of the extension's debug log (default "text")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_format: Option<LogFormat>,
+    /// Days after which files in the extension's log directory are deleted (default 14)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_retention_days: Option<u64>,
+    /// Additional key patterns (e.g. `*_PWD`) whose values are masked in logged configurations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redact_patterns: Option<Vec<String>>,
+    /// Size in MB after which the extension's debug log is rotated (default 5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_max_size_mb: Option<u64>,
+    /// Number of rotated debug log files kept (default 3)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_max_files: Option<usize>,
+}
@@ -0,0 +1,252 @@
+use std::io::Read;
+
+/// Minimal streaming SHA-256 implementation used to verify downloaded binaries
+pub struct Sha256 {
+    state: [u32; 8],
+    buffer: [u8; 64],
+    buffer_len: usize,
+    total_len: u64,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha256 {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+
+    pub fn new() -> Self {
+        Self {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            buffer: [0; 64],
+            buffer_len: 0,
+            total_len: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+
+        if self.buffer_len > 0 {
+            let take = (64 - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+            if self.buffer_len < 64 {
+                return;
+            }
+            let block = self.buffer;
+            self.compress(&block);
+            self.buffer_len = 0;
+        }
+
+        let mut chunks = data.chunks_exact(64);
+        for block in &mut chunks {
+            self.compress(block.try_into().expect("chunk is 64 bytes"));
+        }
+
+        let remainder = chunks.remainder();
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+        self.buffer_len = remainder.len();
+    }
+
+    /// Consumes the hasher and returns the lowercase hex digest
+    pub fn finalize_hex(mut self) -> String {
+        let bit_len = self.total_len.wrapping_mul(8);
+
+        let mut padding = vec![0x80u8];
+        let pad_zeros = (119 - self.buffer_len) % 64;
+        padding.extend(std::iter::repeat_n(0u8, pad_zeros));
+        padding.extend_from_slice(&bit_len.to_be_bytes());
+        // Padding must not count towards the message length
+        let total_len = self.total_len;
+        self.update(&padding);
+        self.total_len = total_len;
+
+        self.state
+            .iter()
+            .map(|word| format!("{:08x}", word))
+            .collect()
+    }
+
+    /// Hashes the contents of the file at the given path
+    pub fn hash_file(path: &std::path::Path) -> Result<String, String> {
+        let mut file = std::fs::File::open(path)
+            .map_err(|e| format!("Failed to open {} for hashing: {}", path.display(), e))?;
+
+        let mut hasher = Self::new();
+        let mut chunk = vec![0u8; 64 * 1024];
+        loop {
+            let read = file
+                .read(&mut chunk)
+                .map_err(|e| format!("Failed to read {} for hashing: {}", path.display(), e))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&chunk[..read]);
+        }
+
+        Ok(hasher.finalize_hex())
+    }
+
+    /// Hashes every file below `dir` into one digest covering their relative paths and
+    /// contents, visiting files in sorted order so that the digest is stable across platforms
+    pub fn hash_dir(dir: &std::path::Path) -> Result<String, String> {
+        fn collect_files(
+            dir: &std::path::Path,
+            files: &mut Vec<std::path::PathBuf>,
+        ) -> Result<(), String> {
+            let entries = std::fs::read_dir(dir)
+                .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+            for entry in entries {
+                let path = entry
+                    .map_err(|e| format!("Failed to read directory entry: {}", e))?
+                    .path();
+                if path.is_dir() {
+                    collect_files(&path, files)?;
+                } else {
+                    files.push(path);
+                }
+            }
+            Ok(())
+        }
+
+        let mut files = Vec::new();
+        collect_files(dir, &mut files)?;
+        let mut entries: Vec<(String, std::path::PathBuf)> = files
+            .into_iter()
+            .filter_map(|path| {
+                let relative = path.strip_prefix(dir).ok()?;
+                let name = relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                Some((name, path))
+            })
+            .collect();
+        entries.sort();
+
+        let mut hasher = Self::new();
+        for (name, path) in entries {
+            hasher.update(name.as_bytes());
+            hasher.update(&[0]);
+            hasher.update(Self::hash_file(&path)?.as_bytes());
+        }
+        Ok(hasher.finalize_hex())
+    }
+
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for (k, w) in Self::K.iter().zip(w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*k)
+                .wrapping_add(w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sha256;
+    use crate::test_support::TempDir;
+
+    fn hex(data: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.finalize_hex()
+    }
+
+    #[test]
+    fn matches_nist_vectors() {
+        assert_eq!(
+            hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            hex(b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"),
+            "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1"
+        );
+    }
+
+    #[test]
+    fn hashes_multi_block_input_fed_in_pieces() {
+        let data = vec![b'a'; 1_000_000];
+        let expected = "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0";
+        assert_eq!(hex(&data), expected);
+
+        let mut hasher = Sha256::new();
+        for piece in data.chunks(997) {
+            hasher.update(piece);
+        }
+        assert_eq!(hasher.finalize_hex(), expected);
+    }
+
+    #[test]
+    fn hashes_files() {
+        let dir = TempDir::new();
+        let path = dir.write("abc.txt", "abc");
+        assert_eq!(
+            Sha256::hash_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(Sha256::hash_file(&dir.path().join("missing")).is_err());
+    }
+}
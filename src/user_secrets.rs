@@ -0,0 +1,108 @@
+use crate::logger::{Logger, ScopedLogger};
+use crate::platform;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use zed_extension_api::serde_json::{self, Value};
+use zed_extension_api::{self as zed};
+
+const LOG: ScopedLogger = Logger::scoped("user_secrets");
+
+/// Separator of configuration sections in environment variable names, which `:` is replaced by
+const SECTION_SEPARATOR: &str = "__";
+
+/// Path of the `secrets.json` file `dotnet user-secrets` manages for `id`: under `%APPDATA%` on
+/// Windows and under the home directory elsewhere
+pub fn secrets_path(id: &str, env: &HashMap<String, String>) -> Option<PathBuf> {
+    let path = match platform::os() {
+        zed::Os::Windows => PathBuf::from(env.get("APPDATA")?)
+            .join("Microsoft")
+            .join("UserSecrets"),
+        zed::Os::Mac | zed::Os::Linux => PathBuf::from(env.get("HOME")?)
+            .join(".microsoft")
+            .join("usersecrets"),
+    };
+    Some(path.join(id).join("secrets.json"))
+}
+
+/// Reads the user secrets stored for `id` as environment variables in the `Section__Key` form
+/// the configuration system reads. A missing or invalid secrets file is reported as a warning,
+/// as the program merely falls back to its other configuration sources.
+pub fn env_vars(id: &str, env: &HashMap<String, String>) -> Vec<(String, Option<String>)> {
+    let Some(path) = secrets_path(id, env) else {
+        LOG.warn(&format!(
+            "Cannot locate the user secrets of '{}': neither APPDATA nor HOME is set",
+            id
+        ));
+        return Vec::new();
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            LOG.warn(&format!(
+                "User secrets of '{}' not loaded from {}: {}. Create them with `dotnet user-secrets set`.",
+                id,
+                path.display(),
+                e
+            ));
+            return Vec::new();
+        }
+    };
+    let content = content.strip_prefix('\u{feff}').unwrap_or(&content);
+    let secrets = match serde_json::from_str::<Value>(content) {
+        Ok(secrets @ Value::Object(_)) => secrets,
+        Ok(_) => {
+            LOG.warn(&format!(
+                "User secrets in {} are not a JSON object; ignoring them",
+                path.display()
+            ));
+            return Vec::new();
+        }
+        Err(e) => {
+            LOG.warn(&format!(
+                "Invalid user secrets in {}: {}; ignoring them",
+                path.display(),
+                e
+            ));
+            return Vec::new();
+        }
+    };
+
+    let mut vars = Vec::new();
+    flatten(&secrets, String::new(), &mut vars);
+    LOG.info(&format!(
+        "Loaded {} user secrets from {}",
+        vars.len(),
+        path.display()
+    ));
+    vars.into_iter()
+        .map(|(key, value)| (key, Some(value)))
+        .collect()
+}
+
+/// Flattens nested objects and arrays into `Section__Key` and `Section__0` names, the way the
+/// JSON configuration provider does with `:`
+fn flatten(value: &Value, prefix: String, vars: &mut Vec<(String, String)>) {
+    let child = |key: &str| {
+        let key = key.replace(':', SECTION_SEPARATOR);
+        if prefix.is_empty() {
+            key
+        } else {
+            format!("{}{}{}", prefix, SECTION_SEPARATOR, key)
+        }
+    };
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                flatten(value, child(key), vars);
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(item, child(&index.to_string()), vars);
+            }
+        }
+        Value::String(value) => vars.push((prefix, value.clone())),
+        Value::Number(_) | Value::Bool(_) => vars.push((prefix, value.to_string())),
+        Value::Null => vars.push((prefix, String::new())),
+    }
+}
@@ -0,0 +1,450 @@
+use crate::logger::{Logger, ScopedLogger};
+use std::sync::OnceLock;
+use zed_extension_api::serde_json::{self, Map, Value};
+
+const LOG: ScopedLogger = Logger::scoped("validation");
+
+/// The schema Zed completes and checks debug configurations with, which also defines the keys
+/// validated here, so that the two cannot drift apart
+const SCHEMA: &str = include_str!("../debug_adapter_schemas/netcoredbg.json");
+
+/// Largest edit distance at which an unknown key or value is assumed to be a typo
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// Requests a key applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Any,
+    Launch,
+    Attach,
+}
+
+/// A key of the debug configuration, as declared in the schema
+#[derive(Debug, Clone)]
+pub struct Key {
+    pub name: String,
+    /// Schema of the key's value
+    pub schema: Value,
+    pub scope: Scope,
+}
+
+/// The keys of the schema and the keys it requires
+struct Registry {
+    keys: Vec<Key>,
+    required: Vec<String>,
+}
+
+/// Keys that set up the same thing in different ways and cannot be combined
+const EXCLUSIVE_KEYS: [(&str, &str); 5] = [
+    ("pipeTransport", "docker"),
+    ("pipeTransport", "wsl"),
+    ("pipeTransport", "useWSL"),
+    ("docker", "wsl"),
+    ("docker", "useWSL"),
+];
+
+/// The keys of the schema: its top-level properties apply to every request, and those of its
+/// `request`-conditional branches to that request, or to any if both branches declare them
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let schema: Value =
+            serde_json::from_str(SCHEMA).expect("the bundled debug adapter schema is valid JSON");
+        let properties = |schema: &Value| -> Vec<(String, Value)> {
+            schema
+                .get("properties")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+                // `true` refers to the top-level declaration of the key
+                .filter(|(_, schema)| schema.is_object())
+                .map(|(name, schema)| (name.clone(), schema.clone()))
+                .collect()
+        };
+
+        let mut keys: Vec<Key> = properties(&schema)
+            .into_iter()
+            .map(|(name, schema)| Key {
+                name,
+                schema,
+                scope: Scope::Any,
+            })
+            .collect();
+        let branches = schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        for branch in branches {
+            let scope = match branch.pointer("/if/properties/request/const") {
+                Some(Value::String(request)) if request == "launch" => Scope::Launch,
+                Some(Value::String(request)) if request == "attach" => Scope::Attach,
+                _ => continue,
+            };
+            for (name, schema) in branch.get("then").map(properties).unwrap_or_default() {
+                match keys.iter_mut().find(|key| key.name == name) {
+                    Some(key) if key.scope != scope => key.scope = Scope::Any,
+                    Some(_) => {}
+                    None => keys.push(Key {
+                        name,
+                        schema,
+                        scope,
+                    }),
+                }
+            }
+        }
+
+        let required = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        Registry { keys, required }
+    })
+}
+
+/// Checks the keys of a debug configuration against the schema before it is parsed: that the
+/// required keys are set, that every known key has a value the schema allows and applies to
+/// the request, and that no exclusive keys are combined. All problems are reported at once,
+/// one per line. Unknown keys, which are passed on to netcoredbg as is, only get a warning
+/// suggesting a similar known key.
+pub fn validate(config: &Map<String, Value>) -> Result<(), String> {
+    let registry = registry();
+    let mut problems: Vec<String> = registry
+        .required
+        .iter()
+        .filter(|name| !config.contains_key(*name))
+        .map(|name| format!("'{}' is required", name))
+        .collect();
+    let request = config.get("request").and_then(Value::as_str);
+
+    for (name, value) in config {
+        let Some(key) = registry.keys.iter().find(|key| &key.name == name) else {
+            LOG.warn(&format!(
+                "Unknown key '{}' is passed on to netcoredbg as is{}",
+                name,
+                suggestion(name, registry.keys.iter().map(|key| key.name.as_str()))
+            ));
+            continue;
+        };
+        if !accepts(&key.schema, value) {
+            let hint = value
+                .as_str()
+                .map(|value| suggestion(value, allowed_strings(&key.schema).into_iter()))
+                .unwrap_or_default();
+            problems.push(format!(
+                "'{}' must be {}, got {}{}",
+                name,
+                describe(&key.schema),
+                value,
+                hint
+            ));
+        }
+        let wrong_request = match key.scope {
+            Scope::Launch if request == Some("attach") => Some("launch"),
+            Scope::Attach if request == Some("launch") => Some("attach"),
+            _ => None,
+        };
+        if let Some(scope) = wrong_request {
+            problems.push(format!(
+                "'{}' only applies to {} configurations; remove it or set 'request' to '{}'",
+                name, scope, scope
+            ));
+        }
+    }
+
+    for (first, second) in EXCLUSIVE_KEYS {
+        if config.contains_key(first) && config.contains_key(second) {
+            problems.push(format!(
+                "'{}' and '{}' cannot be combined, as both set up how netcoredbg is run",
+                first, second
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "Invalid debug configuration:\n{}",
+        problems
+            .iter()
+            .map(|problem| format!("- {}", problem))
+            .collect::<Vec<_>>()
+            .join("\n")
+    ))
+}
+
+/// Whether `value` matches `schema`, as far as its `type`, `enum`, `const`, `oneOf`, `anyOf`
+/// and array `items` tell. Finer constraints are checked where the values are used.
+fn accepts(schema: &Value, value: &Value) -> bool {
+    if let Some(alternatives) = alternatives(schema) {
+        return alternatives
+            .iter()
+            .any(|alternative| accepts(alternative, value));
+    }
+    if let Some(constant) = schema.get("const") {
+        return value == constant;
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        return allowed.contains(value);
+    }
+    let type_matches = match schema.get("type") {
+        Some(Value::String(kind)) => has_type(value, kind),
+        Some(Value::Array(kinds)) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|kind| has_type(value, kind)),
+        _ => true,
+    };
+    let items_match = match (value, schema.get("items")) {
+        (Value::Array(items), Some(item_schema)) => {
+            items.iter().all(|item| accepts(item_schema, item))
+        }
+        _ => true,
+    };
+    type_matches && items_match
+}
+
+fn alternatives(schema: &Value) -> Option<&Vec<Value>> {
+    schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array)
+}
+
+fn has_type(value: &Value, kind: &str) -> bool {
+    match kind {
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// Describes the values `schema` allows, e.g. `a boolean or 'auto'`
+fn describe(schema: &Value) -> String {
+    if let Some(alternatives) = alternatives(schema) {
+        return join_alternatives(alternatives.iter().map(describe).collect());
+    }
+    if let Some(constant) = schema.get("const") {
+        return quote(constant);
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        return format!(
+            "one of {}",
+            allowed.iter().map(quote).collect::<Vec<_>>().join(", ")
+        );
+    }
+    let kinds: Vec<&str> = match schema.get("type") {
+        Some(Value::String(kind)) => vec![kind.as_str()],
+        Some(Value::Array(kinds)) => kinds.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let item_kind = schema.pointer("/items/type").and_then(Value::as_str);
+    join_alternatives(
+        kinds
+            .into_iter()
+            .map(|kind| match (kind, item_kind) {
+                ("array", Some(item_kind)) => format!("an array of {}s", item_kind),
+                ("array" | "object" | "integer", _) => format!("an {}", kind),
+                (kind, _) => format!("a {}", kind),
+            })
+            .collect(),
+    )
+}
+
+fn join_alternatives(mut alternatives: Vec<String>) -> String {
+    match alternatives.pop() {
+        Some(last) if !alternatives.is_empty() => {
+            format!("{} or {}", alternatives.join(", "), last)
+        }
+        Some(last) => last,
+        None => "a valid value".to_string(),
+    }
+}
+
+fn quote(value: &Value) -> String {
+    match value {
+        Value::String(value) => format!("'{}'", value),
+        other => other.to_string(),
+    }
+}
+
+/// The strings `schema` allows through `enum` or `const`, for suggesting a misspelled one
+fn allowed_strings(schema: &Value) -> Vec<&str> {
+    let mut allowed: Vec<&str> = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .chain(schema.get("const"))
+        .filter_map(Value::as_str)
+        .collect();
+    for alternative in alternatives(schema).into_iter().flatten() {
+        allowed.extend(allowed_strings(alternative));
+    }
+    allowed
+}
+
+/// `; did you mean '<candidate>'?` for the candidate closest to `input`, if one is close enough
+fn suggestion<'a>(input: &str, candidates: impl Iterator<Item = &'a str>) -> String {
+    let input = input.to_ascii_lowercase();
+    candidates
+        .map(|candidate| {
+            (
+                edit_distance(&input, &candidate.to_ascii_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|&(distance, _)| distance <= MAX_SUGGESTION_DISTANCE.min(input.len() / 2))
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| format!("; did you mean '{}'?", candidate))
+        .unwrap_or_default()
+}
+
+/// Levenshtein distance between `a` and `b`, counting characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope_of(name: &str) -> Option<Scope> {
+        registry()
+            .keys
+            .iter()
+            .find(|key| key.name == name)
+            .map(|key| key.scope)
+    }
+
+    fn config(json: Value) -> Map<String, Value> {
+        match json {
+            Value::Object(object) => object,
+            other => panic!("not an object: {other}"),
+        }
+    }
+
+    #[test]
+    fn registers_the_keys_of_the_schema() {
+        let cases = [
+            ("request", Some(Scope::Any)),
+            ("adapterArgs", Some(Scope::Any)),
+            ("program", Some(Scope::Launch)),
+            ("cwd", Some(Scope::Launch)),
+            ("useApphost", Some(Scope::Launch)),
+            ("processId", Some(Scope::Attach)),
+            ("processName", Some(Scope::Attach)),
+            // Declared by both request branches
+            ("justMyCode", Some(Scope::Any)),
+            ("pipeTransport", Some(Scope::Any)),
+            ("programm", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(scope_of(name), expected, "key: {name}");
+        }
+        assert_eq!(registry().required, ["request"]);
+    }
+
+    #[test]
+    fn declares_exclusive_keys_in_the_schema() {
+        // Exclusive keys can only be combined if both are known
+        for (first, second) in EXCLUSIVE_KEYS {
+            assert!(scope_of(first).is_some(), "exclusive key: {first}");
+            assert!(scope_of(second).is_some(), "exclusive key: {second}");
+        }
+    }
+
+    #[test]
+    fn reports_every_problem_at_once() {
+        assert_eq!(
+            validate(&config(serde_json::json!({
+                "request": "launch",
+                "program": "${auto}",
+                "useApphost": "auto",
+                "unknownKey": 1
+            }))),
+            Ok(())
+        );
+        let error = validate(&config(serde_json::json!({
+            "request": "launch",
+            "justMyCode": "yes",
+            "useApphost": "autoo",
+            "processId": 42,
+            "pipeTransport": {},
+            "wsl": {}
+        })))
+        .unwrap_err();
+        let expected = [
+            "Invalid debug configuration:",
+            "- 'justMyCode' must be a boolean, got \"yes\"",
+            "- 'processId' only applies to attach configurations; remove it or set 'request' to 'attach'",
+            "- 'useApphost' must be a boolean or 'auto', got \"autoo\"; did you mean 'auto'?",
+            "- 'pipeTransport' and 'wsl' cannot be combined, as both set up how netcoredbg is run",
+        ];
+        assert_eq!(error, expected.join("\n"));
+        assert_eq!(
+            validate(&Map::new()).unwrap_err(),
+            "Invalid debug configuration:\n- 'request' is required"
+        );
+    }
+
+    #[test]
+    fn suggests_close_candidates() {
+        let keys = ["program", "processId", "processName", "cwd", "env"];
+        let cases = [
+            ("programm", "; did you mean 'program'?"),
+            ("Program", "; did you mean 'program'?"),
+            ("procesId", "; did you mean 'processId'?"),
+            ("processname", "; did you mean 'processName'?"),
+            // Short inputs only match within half their length
+            ("cd", "; did you mean 'cwd'?"),
+            ("evn", ""),
+            ("environment", ""),
+            ("", ""),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                suggestion(input, keys.into_iter()),
+                expected,
+                "input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn counts_edits_between_strings() {
+        let cases = [
+            ("", "", 0),
+            ("abc", "", 3),
+            ("", "abc", 3),
+            ("kitten", "sitting", 3),
+            ("flaw", "lawn", 2),
+            ("wsl", "wsl", 0),
+            ("ünïcode", "unicode", 2),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(edit_distance(a, b), expected, "input: {a} {b}");
+            assert_eq!(edit_distance(b, a), expected, "input: {b} {a}");
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::processes::PICK_PROCESS;
+use crate::project::AUTO_PROGRAM;
+use std::collections::HashMap;
+use zed_extension_api::serde_json::Value;
+
+/// References resolved later by the extension itself, which the substitution leaves in place
+const DEFERRED: [&str; 3] = [AUTO_PROGRAM, PICK_PROCESS, "${debuggerCommand}"];
+
+/// VS Code variables resolved in the strings of a debug configuration
+pub struct Variables {
+    worktree_root: String,
+    env: HashMap<String, String>,
+}
+
+impl Variables {
+    pub fn new(worktree_root: String, env: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self {
+            worktree_root,
+            env: env.into_iter().collect(),
+        }
+    }
+
+    /// Substitutes variables in every string of `value`, including those nested in arrays and
+    /// objects. `field` names `value` in error messages.
+    pub fn substitute(&self, value: &mut Value, field: &str) -> Result<(), String> {
+        match value {
+            Value::String(string) => *string = self.substitute_str(string, field)?,
+            Value::Array(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    self.substitute(item, &format!("{}[{}]", field, index))?;
+                }
+            }
+            Value::Object(object) => {
+                for (key, item) in object.iter_mut() {
+                    let field = if field.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", field, key)
+                    };
+                    self.substitute(item, &field)?;
+                }
+            }
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Replaces `${name}` references in `input`; `$${` yields a literal `${`. References to
+    /// environment variables without `env:`, such as `${HOME}`, are left for path expansion.
+    fn substitute_str(&self, input: &str, field: &str) -> Result<String, String> {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                output.push_str(&rest[..start - 1]);
+                output.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            output.push_str(&rest[..start]);
+            let Some(length) = rest[start..].find('}') else {
+                return Err(format!("Unterminated '${{' in '{}': {}", field, input));
+            };
+            let reference = &rest[start..start + length + 1];
+            let name = &reference[2..reference.len() - 1];
+            match self.resolve(name, reference) {
+                Some(value) => output.push_str(&value),
+                None => {
+                    return Err(format!(
+                        "Unknown variable '{}' in '{}'. Supported: ${{workspaceFolder}}, ${{workspaceFolderBasename}}, ${{cwd}} and ${{env:NAME}}; use $ZED_FILE and $ZED_DIRNAME for the current file, or $${{ for a literal '${{'.",
+                        reference, field
+                    ))
+                }
+            }
+            rest = &rest[start + length + 1..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    fn resolve(&self, name: &str, reference: &str) -> Option<String> {
+        match name {
+            "workspaceFolder" | "workspaceRoot" | "cwd" | "ZED_WORKTREE_ROOT" => {
+                Some(self.worktree_root.clone())
+            }
+            "workspaceFolderBasename" => Some(
+                self.worktree_root
+                    .trim_end_matches(['/', '\\'])
+                    .rsplit(['/', '\\'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+            _ if DEFERRED.contains(&reference) => Some(reference.to_string()),
+            _ => match name.strip_prefix("env:") {
+                Some(var) => self.env.get(var).cloned(),
+                None if self.env.contains_key(name) => Some(reference.to_string()),
+                None => None,
+            },
+        }
+    }
+}
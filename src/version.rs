@@ -0,0 +1,86 @@
+use std::cmp::Ordering;
+
+/// Compares two netcoredbg release tags (e.g. "3.1.2-1054") segment by segment.
+/// Segments are separated by `.`, `-`, `_` or `+`; numeric segments compare numerically,
+/// anything else falls back to plain string comparison so malformed tags never panic.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut a_segments = segments(a);
+    let mut b_segments = segments(b);
+
+    loop {
+        match (a_segments.next(), b_segments.next()) {
+            (None, None) => return Ordering::Equal,
+            // A trailing pre-release label (e.g. "-rc1") sorts before the plain release
+            (Some(extra), None) => return trailing_ordering(extra),
+            (None, Some(extra)) => return trailing_ordering(extra).reverse(),
+            (Some(a_segment), Some(b_segment)) => {
+                let ordering = match (a_segment.parse::<u64>(), b_segment.parse::<u64>()) {
+                    (Ok(a_number), Ok(b_number)) => a_number.cmp(&b_number),
+                    _ => a_segment.cmp(b_segment),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+/// Whether a tag carries a pre-release label such as `rc1`, `beta` or `preview`
+pub fn is_prerelease(tag: &str) -> bool {
+    segments(tag).any(|segment| segment.chars().any(|c| c.is_ascii_alphabetic()))
+}
+
+fn trailing_ordering(extra_segment: &str) -> Ordering {
+    if extra_segment.parse::<u64>().is_ok() {
+        Ordering::Greater
+    } else {
+        Ordering::Less
+    }
+}
+
+fn segments(tag: &str) -> impl Iterator<Item = &str> {
+    tag.trim_start_matches(['v', 'V'])
+        .split(['.', '-', '_', '+'])
+        .filter(|segment| !segment.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_numeric_segments_numerically() {
+        assert_eq!(
+            compare_versions("3.1.2-1054", "3.1.2-1054"),
+            Ordering::Equal
+        );
+        assert_eq!(compare_versions("3.1.10-1", "3.1.9-1"), Ordering::Greater);
+        assert_eq!(compare_versions("3.1.2-999", "3.1.2-1054"), Ordering::Less);
+        assert_eq!(compare_versions("v3.1.2", "3.1.2"), Ordering::Equal);
+        assert_eq!(compare_versions("3.1", "3.1.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn sorts_prerelease_before_release() {
+        assert_eq!(compare_versions("3.2.0-rc1", "3.2.0"), Ordering::Less);
+        assert_eq!(compare_versions("3.2.0", "3.2.0-rc1"), Ordering::Greater);
+        assert_eq!(compare_versions("3.2.0-rc1", "3.2.0-rc2"), Ordering::Less);
+        assert_eq!(compare_versions("3.2.0-rc1", "3.1.9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn compares_malformed_tags_without_panicking() {
+        assert_eq!(compare_versions("", ""), Ordering::Equal);
+        assert_eq!(compare_versions("nightly", "3.1.2"), "nightly".cmp("3.1.2"));
+        assert_eq!(compare_versions("3..1", "3.1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn detects_prerelease_labels() {
+        assert!(is_prerelease("3.2.0-rc1"));
+        assert!(is_prerelease("v3.2.0-beta"));
+        assert!(!is_prerelease("3.1.2-1054"));
+        assert!(!is_prerelease("v3.1.2"));
+    }
+}
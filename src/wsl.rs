@@ -0,0 +1,138 @@
+use crate::logger::{Logger, ScopedLogger};
+use crate::paths;
+use crate::PipeTransport;
+use serde::{Deserialize, Serialize};
+use zed_extension_api::process::Command;
+use zed_extension_api::serde_json::{self, Value};
+
+const LOG: ScopedLogger = Logger::scoped("wsl");
+
+/// Where the netcoredbg release for Linux can be downloaded for installing it in a distribution
+const RELEASES_URL: &str = "https://github.com/Samsung/netcoredbg/releases";
+
+/// A WSL distribution netcoredbg runs in, so that .NET processes of the distribution can be
+/// debugged from Zed on Windows
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WslTarget {
+    /// Distribution to run in; defaults to the default distribution of `wsl.exe`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<String>,
+    /// Path of netcoredbg inside the distribution; looked up on its `PATH` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debugger_path: Option<String>,
+}
+
+impl WslTarget {
+    /// The pipe transport running netcoredbg in the distribution through `wsl.exe`
+    pub fn pipe_transport(&self) -> Result<PipeTransport, String> {
+        let debugger_path = match &self.debugger_path {
+            Some(debugger_path) => debugger_path.clone(),
+            None => self.locate_debugger()?,
+        };
+        LOG.info(&format!(
+            "Running netcoredbg at {} in {}",
+            debugger_path,
+            self.describe()
+        ));
+        Ok(PipeTransport {
+            pipe_program: Some("wsl.exe".to_string()),
+            pipe_args: self.wsl_args(),
+            debugger_path: Some(debugger_path),
+            pipe_cwd: None,
+            // `--exec` runs the command without a shell, so arguments must not be quoted
+            quote_args: Some(false),
+        })
+    }
+
+    /// Maps the path the distribution sees the worktree `root` at back to `root`, since
+    /// sources compiled in the distribution are recorded at their `/mnt/<drive>` paths
+    pub fn source_file_map(root: &str) -> Option<serde_json::Map<String, Value>> {
+        paths::to_wsl_path(root).map(|wsl_root| {
+            [(wsl_root, Value::String(root.to_string()))]
+                .into_iter()
+                .collect()
+        })
+    }
+
+    /// Arguments of `wsl.exe` selecting the distribution and running the following command
+    /// without a shell
+    fn wsl_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(distribution) = &self.distribution {
+            args.push("-d".to_string());
+            args.push(distribution.clone());
+        }
+        args.push("--exec".to_string());
+        args
+    }
+
+    /// Looks up netcoredbg on the distribution's `PATH`
+    fn locate_debugger(&self) -> Result<String, String> {
+        let output = Command::new("wsl.exe")
+            .args(self.wsl_args())
+            .args(["which", "netcoredbg"])
+            .output()
+            .map_err(|e| format!("Failed to run wsl.exe: {}. Is WSL installed?", e))?;
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status != Some(0) || path.is_empty() {
+            return Err(format!(
+                "netcoredbg was not found on the PATH of {}. Install the Linux release from {} there, or set 'wsl.debuggerPath'.",
+                self.describe(),
+                RELEASES_URL
+            ));
+        }
+        Ok(path)
+    }
+
+    fn describe(&self) -> String {
+        match &self.distribution {
+            Some(distribution) => format!("WSL distribution '{}'", distribution),
+            None => "the default WSL distribution".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_netcoredbg_through_wsl_exe() {
+        let target = WslTarget {
+            distribution: Some("Ubuntu-22.04".to_string()),
+            debugger_path: Some("/usr/local/bin/netcoredbg".to_string()),
+        };
+        let pipe_transport = target.pipe_transport().unwrap();
+        assert_eq!(pipe_transport.pipe_program.as_deref(), Some("wsl.exe"));
+        assert_eq!(pipe_transport.pipe_args, ["-d", "Ubuntu-22.04", "--exec"]);
+        assert_eq!(
+            pipe_transport.debugger_path.as_deref(),
+            Some("/usr/local/bin/netcoredbg")
+        );
+        assert_eq!(pipe_transport.quote_args, Some(false));
+
+        assert_eq!(WslTarget::default().wsl_args(), ["--exec"]);
+    }
+
+    #[test]
+    fn maps_sources_back_to_the_worktree() {
+        let cases = [
+            (r"C:\src\App", Some(("/mnt/c/src/App", r"C:\src\App"))),
+            ("/C:/src/App", Some(("/mnt/c/src/App", "/C:/src/App"))),
+            (
+                r"\\wsl$\Ubuntu\home\me\App",
+                Some(("/home/me/App", r"\\wsl$\Ubuntu\home\me\App")),
+            ),
+            (r"\\server\share\App", None),
+        ];
+        for (root, expected) in cases {
+            let expected = expected.map(|(wsl_root, root)| {
+                [(wsl_root.to_string(), Value::String(root.to_string()))]
+                    .into_iter()
+                    .collect()
+            });
+            assert_eq!(WslTarget::source_file_map(root), expected, "root: {root}");
+        }
+    }
+}